@@ -1,11 +1,37 @@
+use crate::text::{first_sentence, truncate};
+
+/// 摘要中正文部分最多保留的字素数
+pub const EXCERPT_LEN: usize = 100;
+
 pub trait Summary {
     fn summarize_author(&self) -> String;
 
     fn summarize(&self) -> String {
         format!("(Read more from {}...)", self.summarize_author())
     }
+
+    /// 截断到最多 `max` 个字素的摘要, 被截断时以 `…` 结尾
+    fn summarize_to(&self, max: usize) -> String {
+        truncate(&self.summarize(), max).into_owned()
+    }
+
+    /// 用来比较内容是否相似的正文, 默认就是摘要
+    fn content(&self) -> String {
+        self.summarize()
+    }
+
+    /// 建立搜索索引时使用的文本, 默认就是正文
+    fn search_text(&self) -> String {
+        self.content()
+    }
+
+    /// 不含作者的摘要, 用在已经单独显示了作者的地方; 默认就是摘要
+    fn summarize_body(&self) -> String {
+        self.summarize()
+    }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct NewsArticle {
     pub headline: String,
     pub location: String,
@@ -17,8 +43,40 @@ impl Summary for NewsArticle {
     fn summarize_author(&self) -> String {
         format!("@{}", self.author)
     }
+
+    /// `标题, by 作者 (地点): 正文第一句`
+    fn summarize(&self) -> String {
+        self.with_excerpt(format!(
+            "{}, by {} ({})",
+            self.headline, self.author, self.location
+        ))
+    }
+
+    /// `标题 (地点): 正文第一句`
+    fn summarize_body(&self) -> String {
+        self.with_excerpt(format!("{} ({})", self.headline, self.location))
+    }
+
+    fn content(&self) -> String {
+        self.content.clone()
+    }
+
+    fn search_text(&self) -> String {
+        format!("{}\n{}", self.headline, self.content)
+    }
+}
+
+impl NewsArticle {
+    /// 正文不为空时在 `byline` 后面加上正文第一句
+    fn with_excerpt(&self, byline: String) -> String {
+        match first_sentence(&self.content) {
+            "" => byline,
+            sentence => format!("{}: {}", byline, truncate(sentence, EXCERPT_LEN)),
+        }
+    }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Tweet {
     pub username: String,
     pub content: String,
@@ -30,4 +88,150 @@ impl Summary for Tweet {
     fn summarize_author(&self) -> String {
         format!("@{}", self.username)
     }
+
+    /// `[RT] [reply] @用户: 内容`, 转推和回复才有对应的标记
+    fn summarize(&self) -> String {
+        format!(
+            "{}{}: {}",
+            self.markers(),
+            self.summarize_author(),
+            truncate(self.content.trim(), EXCERPT_LEN)
+        )
+    }
+
+    /// `[RT] [reply] 内容`
+    fn summarize_body(&self) -> String {
+        format!(
+            "{}{}",
+            self.markers(),
+            truncate(self.content.trim(), EXCERPT_LEN)
+        )
+    }
+
+    fn content(&self) -> String {
+        self.content.clone()
+    }
+}
+
+impl Tweet {
+    fn markers(&self) -> String {
+        let mut markers = String::new();
+        if self.retweet {
+            markers.push_str("[RT] ");
+        }
+        if self.reply {
+            markers.push_str("[reply] ");
+        }
+        markers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(content: &str) -> NewsArticle {
+        NewsArticle {
+            headline: String::from("Penguins win the Stanley Cup Championship!"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from("Iceburgh"),
+            content: String::from(content),
+        }
+    }
+
+    fn tweet(content: &str, reply: bool, retweet: bool) -> Tweet {
+        Tweet {
+            username: String::from("horse_ebooks"),
+            content: String::from(content),
+            reply,
+            retweet,
+        }
+    }
+
+    #[test]
+    fn article_uses_first_sentence() {
+        let article = article(
+            "The Pittsburgh Penguins once again are the best hockey team in the NHL. They won 4-2.",
+        );
+
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship!, by Iceburgh (Pittsburgh, PA, USA): \
+             The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            article.summarize()
+        );
+    }
+
+    #[test]
+    fn article_without_content() {
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship!, by Iceburgh (Pittsburgh, PA, USA)",
+            article("  ").summarize()
+        );
+    }
+
+    #[test]
+    fn article_excerpt_is_truncated() {
+        let summary = article(&"企鹅".repeat(100)).summarize();
+        let excerpt = summary.split(": ").nth(1).unwrap();
+
+        assert_eq!(EXCERPT_LEN, excerpt.chars().count());
+        assert!(excerpt.ends_with("企…"));
+    }
+
+    #[test]
+    fn tweet_markers() {
+        let text = "of course, as you probably already know, people";
+
+        assert_eq!(
+            "@horse_ebooks: of course, as you probably already know, people",
+            tweet(text, false, false).summarize()
+        );
+        assert_eq!(
+            "[reply] @horse_ebooks: of course, as you probably already know, people",
+            tweet(text, true, false).summarize()
+        );
+        assert_eq!(
+            "[RT] [reply] @horse_ebooks: of course, as you probably already know, people",
+            tweet(text, true, true).summarize()
+        );
+    }
+
+    #[test]
+    fn summarize_to_limits_length() {
+        let tweet = tweet("你好，世界！今天天气很好。", false, true);
+
+        assert_eq!("[RT] @horse_ebooks: 你好，…", tweet.summarize_to(24));
+        assert_eq!(tweet.summarize(), tweet.summarize_to(100));
+    }
+
+    #[test]
+    fn body_leaves_out_the_author() {
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship! (Pittsburgh, PA, USA): They won.",
+            article("They won. 4-2.").summarize_body()
+        );
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship! (Pittsburgh, PA, USA)",
+            article("").summarize_body()
+        );
+        assert_eq!(
+            "[RT] [reply] 你好，世界！",
+            tweet("  你好，世界！  ", true, true).summarize_body()
+        );
+    }
+
+    #[test]
+    fn default_summary_mentions_author() {
+        struct Anonymous;
+
+        impl Summary for Anonymous {
+            fn summarize_author(&self) -> String {
+                String::from("@anon")
+            }
+        }
+
+        assert_eq!("(Read more from @anon...)", Anonymous.summarize());
+        assert_eq!("(Read more…", Anonymous.summarize_to(11));
+        assert_eq!(Anonymous.summarize(), Anonymous.content());
+    }
 }
@@ -1,23 +1,9 @@
-fn main1() {
-    let number_list = vec![34, 50, 25, 100, 65];
-
-    let mut largest = number_list[0];
-
-    for number in number_list {
-        if number > largest {
-            largest = number;
-        }
-    }
-
-    println!("The largest number is {}", largest);
-}
-
 pub fn largest<T: PartialOrd>(list: &[T]) -> &T {
     let mut largest = &list[0];
 
     for item in list {
         if item > largest {
-            largest = &item;
+            largest = item;
         }
     }
 
@@ -35,13 +21,3 @@ pub fn main2() {
     let result = largest(&char_list);
     println!("The largest char is {}", result);
 }
-
-struct Point<T> {
-    x: T,
-    y: T,
-}
-
-fn main3() {
-    let integer = Point { x: 5, y: 10 };
-    let float = Point { x: 1.0, y: 4.0 };
-}
@@ -0,0 +1,417 @@
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::aggregator::Summary;
+use crate::similarity::Deduplicator;
+
+/// 信息流中的一条内容及其发布时间
+pub struct Entry {
+    pub published: SystemTime,
+    pub item: Box<dyn Summary>,
+}
+
+impl Entry {
+    pub fn author(&self) -> String {
+        self.item.summarize_author()
+    }
+
+    pub fn summary(&self) -> String {
+        self.item.summarize()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    NewestFirst,
+    OldestFirst,
+    /// 按作者排序, 同一作者的内容新的在前
+    Author,
+}
+
+/// 收集实现了 `Summary` 的各种内容, 用来排序、筛选、分页并生成摘要
+#[derive(Default)]
+pub struct Feed {
+    entries: Vec<Entry>,
+}
+
+impl Feed {
+    pub fn new() -> Feed {
+        Feed::default()
+    }
+
+    pub fn push(&mut self, item: impl Summary + 'static, published: SystemTime) {
+        self.entries.push(Entry {
+            published,
+            item: Box::new(item),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn sort(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::NewestFirst => self.entries.sort_by_key(|entry| Reverse(entry.published)),
+            SortOrder::OldestFirst => self.entries.sort_by_key(|entry| entry.published),
+            SortOrder::Author => self.entries.sort_by(|a, b| {
+                a.author()
+                    .cmp(&b.author())
+                    .then(b.published.cmp(&a.published))
+            }),
+        }
+    }
+
+    /// 某个作者的内容, `author` 带不带开头的 `@` 均可
+    pub fn by_author<'a>(&'a self, author: &'a str) -> impl Iterator<Item = &'a Entry> + 'a {
+        let author = author.trim_start_matches('@');
+        self.entries
+            .iter()
+            .filter(move |entry| entry.author().trim_start_matches('@') == author)
+    }
+
+    /// 相似的内容只保留排在最前面的一条, 可以交给 `paginate` 生成去重后的摘要
+    pub fn distinct(&self, dedup: &Deduplicator) -> Vec<&Entry> {
+        let items: Vec<&dyn Summary> = self
+            .entries
+            .iter()
+            .map(|entry| entry.item.as_ref())
+            .collect();
+        dedup
+            .clusters(&items)
+            .into_iter()
+            .map(|cluster| &self.entries[cluster[0]])
+            .collect()
+    }
+
+    pub fn page(&self, number: usize, size: usize) -> Page<'_> {
+        paginate(self.entries.iter(), number, size)
+    }
+}
+
+/// 分页结果, 页码从 1 开始
+pub struct Page<'a> {
+    pub entries: Vec<&'a Entry>,
+    pub number: usize,
+    pub total_pages: usize,
+    pub total_entries: usize,
+}
+
+impl Page<'_> {
+    pub fn has_next(&self) -> bool {
+        self.number < self.total_pages
+    }
+
+    /// 本页的内容, 可以交给 `Renderer` 渲染
+    pub fn items(&self) -> Vec<&dyn Summary> {
+        self.entries
+            .iter()
+            .map(|entry| entry.item.as_ref())
+            .collect()
+    }
+
+    /// 生成本页的摘要文本, 时间为 UTC
+    ///
+    /// ```text
+    /// Weekly (page 1 of 2)
+    ///
+    /// 2024-05-01 08:30 @horse_ebooks
+    ///   of course, as you probably already know, people
+    /// ```
+    pub fn digest(&self, title: &str) -> String {
+        let mut out = format!(
+            "{} (page {} of {})\n",
+            title,
+            self.number,
+            self.total_pages.max(1)
+        );
+
+        for entry in &self.entries {
+            write!(
+                out,
+                "\n{} {}\n  {}\n",
+                format_time(entry.published),
+                entry.author(),
+                entry.item.summarize_body()
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+/// 把任意一组内容分页, 可以先用 `Feed::by_author` 等筛选; `size` 为 0 时视为 1
+pub fn paginate<'a>(
+    entries: impl IntoIterator<Item = &'a Entry>,
+    number: usize,
+    size: usize,
+) -> Page<'a> {
+    let size = size.max(1);
+    let number = number.max(1);
+    let all: Vec<&Entry> = entries.into_iter().collect();
+
+    Page {
+        entries: all
+            .iter()
+            // 页码很大时相乘会溢出, 饱和到 usize::MAX 即得到空页
+            .skip((number - 1).saturating_mul(size))
+            .take(size)
+            .copied()
+            .collect(),
+        number,
+        total_pages: all.len().div_ceil(size),
+        total_entries: all.len(),
+    }
+}
+
+/// 格式化为 `YYYY-MM-DD HH:MM` (UTC), 早于 1970 年的时间按 1970 年处理
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let minutes = secs % 86_400 / 60;
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// 从 1970-01-01 起的天数换算为公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::{NewsArticle, Tweet};
+    use crate::render::{MarkdownRenderer, Renderer};
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn tweet(username: &str) -> Tweet {
+        Tweet {
+            username: String::from(username),
+            content: String::from("of course, as you probably already know, people"),
+            reply: false,
+            retweet: false,
+        }
+    }
+
+    fn article(author: &str) -> NewsArticle {
+        NewsArticle {
+            headline: String::from("Penguins win the Stanley Cup Championship!"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from(author),
+            content: String::from(
+                "The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            ),
+        }
+    }
+
+    /// 2024-05-01 08:30 起每隔一小时一条
+    fn sample() -> Feed {
+        let start = 1_714_552_200;
+        let mut feed = Feed::new();
+        feed.push(tweet("horse_ebooks"), at(start + 3_600));
+        feed.push(article("Iceburgh"), at(start));
+        feed.push(tweet("horse_ebooks"), at(start + 7_200));
+        feed.push(tweet("rustlang"), at(start + 10_800));
+        feed
+    }
+
+    fn times(feed: &Feed) -> Vec<String> {
+        feed.iter()
+            .map(|entry| format_time(entry.published))
+            .collect()
+    }
+
+    #[test]
+    fn sort_by_time() {
+        let mut feed = sample();
+
+        feed.sort(SortOrder::OldestFirst);
+        assert_eq!(
+            vec![
+                "2024-05-01 08:30",
+                "2024-05-01 09:30",
+                "2024-05-01 10:30",
+                "2024-05-01 11:30"
+            ],
+            times(&feed)
+        );
+
+        feed.sort(SortOrder::NewestFirst);
+        assert_eq!("2024-05-01 11:30", times(&feed)[0]);
+    }
+
+    #[test]
+    fn sort_by_author() {
+        let mut feed = sample();
+
+        feed.sort(SortOrder::Author);
+
+        let authors: Vec<String> = feed.iter().map(Entry::author).collect();
+        assert_eq!(
+            vec!["@Iceburgh", "@horse_ebooks", "@horse_ebooks", "@rustlang"],
+            authors
+        );
+        assert_eq!("2024-05-01 10:30", times(&feed)[1]);
+    }
+
+    #[test]
+    fn filter_by_author() {
+        let feed = sample();
+
+        assert_eq!(2, feed.by_author("horse_ebooks").count());
+        assert_eq!(2, feed.by_author("@horse_ebooks").count());
+        assert_eq!(0, feed.by_author("nobody").count());
+    }
+
+    #[test]
+    fn paging() {
+        let feed = sample();
+
+        let first = feed.page(1, 3);
+        assert_eq!(3, first.entries.len());
+        assert_eq!((2, 4), (first.total_pages, first.total_entries));
+        assert!(first.has_next());
+
+        let second = feed.page(2, 3);
+        assert_eq!(1, second.entries.len());
+        assert!(!second.has_next());
+
+        assert!(feed.page(3, 3).entries.is_empty());
+        assert_eq!(4, feed.page(0, 0).total_pages);
+    }
+
+    #[test]
+    fn digest_of_filtered_page() {
+        let feed = sample();
+
+        let page = paginate(feed.by_author("horse_ebooks"), 1, 10);
+
+        assert_eq!(
+            "\
+Weekly (page 1 of 1)
+
+2024-05-01 09:30 @horse_ebooks
+  of course, as you probably already know, people
+
+2024-05-01 10:30 @horse_ebooks
+  of course, as you probably already know, people
+",
+            page.digest("Weekly")
+        );
+    }
+
+    #[test]
+    fn render_page() {
+        let feed = sample();
+
+        let markdown = MarkdownRenderer.render("Weekly", &feed.page(2, 3).items());
+
+        assert_eq!(
+            "# Weekly\n\n- **@rustlang**: of course, as you probably already know, people\n",
+            markdown
+        );
+    }
+
+    #[test]
+    fn digest_shows_one_entry_per_cluster() {
+        let mut feed = sample();
+        feed.sort(SortOrder::NewestFirst);
+
+        let distinct = feed.distinct(&Deduplicator::default());
+        let authors: Vec<String> = distinct.iter().map(|entry| entry.author()).collect();
+        assert_eq!(vec!["@rustlang", "@Iceburgh"], authors);
+
+        let page = paginate(distinct, 1, 10);
+        assert_eq!(2, page.total_entries);
+        assert!(page.digest("Weekly").contains("2024-05-01 11:30 @rustlang"));
+    }
+
+    #[test]
+    fn huge_page_numbers_are_empty() {
+        let feed = sample();
+
+        let page = feed.page(usize::MAX, 10);
+        assert!(page.entries.is_empty());
+        assert_eq!(usize::MAX, page.number);
+        assert_eq!(1, page.total_pages);
+        assert!(!page.has_next());
+
+        assert!(feed.page(usize::MAX, usize::MAX).entries.is_empty());
+        assert!(feed.page(2, usize::MAX).entries.is_empty());
+    }
+
+    #[test]
+    fn digest_keeps_markers_and_excerpts() {
+        let mut feed = Feed::new();
+        feed.push(article("Iceburgh"), at(1_714_552_200));
+        feed.push(
+            Tweet {
+                reply: true,
+                retweet: true,
+                ..tweet("rustlang")
+            },
+            at(1_714_555_800),
+        );
+
+        assert_eq!(
+            "\
+Weekly (page 1 of 1)
+
+2024-05-01 08:30 @Iceburgh
+  Penguins win the Stanley Cup Championship! (Pittsburgh, PA, USA): The Pittsburgh Penguins once again are the best hockey team in the NHL.
+
+2024-05-01 09:30 @rustlang
+  [RT] [reply] of course, as you probably already know, people
+",
+            feed.page(1, 10).digest("Weekly")
+        );
+    }
+
+    #[test]
+    fn digest_of_empty_feed() {
+        assert_eq!(
+            "Empty (page 1 of 1)\n",
+            Feed::new().page(1, 10).digest("Empty")
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!("1970-01-01 00:00", format_time(UNIX_EPOCH));
+        assert_eq!("2000-02-29 23:59", format_time(at(951_868_740)));
+        assert_eq!("2024-12-31 12:00", format_time(at(1_735_646_400)));
+    }
+}
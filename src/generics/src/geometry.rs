@@ -0,0 +1,324 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+pub use polygon::{Polygon, Polyline};
+
+pub mod polygon;
+
+/// 平面上的点, 也可以当作从原点出发的向量
+///
+/// 两个坐标默认是同一类型; 只有 `mixup` 会产生坐标类型不同的点.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point<X, Y = X> {
+    pub x: X,
+    pub y: Y,
+}
+
+/// 可以作为坐标的数值类型: 有符号整数和浮点数
+///
+/// 无符号整数相减可能溢出, 不适合表示向量, 因此没有实现.
+pub trait Coordinate:
+    Copy
+    + PartialOrd
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    fn to_f64(self) -> f64;
+}
+
+/// 可以开平方的坐标类型
+pub trait Float: Coordinate + Div<Output = Self> {
+    fn sqrt(self) -> Self;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => {
+        $(impl Coordinate for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+coordinate!(i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl Float for f32 {
+    fn sqrt(self) -> f32 {
+        f32::sqrt(self)
+    }
+}
+
+impl Float for f64 {
+    fn sqrt(self) -> f64 {
+        f64::sqrt(self)
+    }
+}
+
+impl<X, Y> Point<X, Y> {
+    pub fn new(x: X, y: Y) -> Point<X, Y> {
+        Point { x, y }
+    }
+
+    pub fn x(&self) -> &X {
+        &self.x
+    }
+
+    pub fn y(&self) -> &Y {
+        &self.y
+    }
+
+    /// 取自己的 x 和 `other` 的 y 组成新的点, 两者的坐标类型可以不同
+    pub fn mixup<X2, Y2>(self, other: Point<X2, Y2>) -> Point<X, Y2> {
+        Point {
+            x: self.x,
+            y: other.y,
+        }
+    }
+}
+
+impl<T> Point<T> {
+    /// 无损地转换坐标类型, 例如 `Point<i32>` 转为 `Point<f64>`
+    pub fn cast<U: From<T>>(self) -> Point<U> {
+        Point {
+            x: U::from(self.x),
+            y: U::from(self.y),
+        }
+    }
+
+    /// 可能失败的坐标类型转换, 例如 `Point<i64>` 转为 `Point<i32>`
+    pub fn try_cast<U: TryFrom<T>>(self) -> Result<Point<U>, U::Error> {
+        Ok(Point {
+            x: U::try_from(self.x)?,
+            y: U::try_from(self.y)?,
+        })
+    }
+}
+
+impl<T: Coordinate> Point<T> {
+    pub fn dot(self, other: Point<T>) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// 二维叉积(即三维叉积的 z 分量): 为正时 `other` 在自己的逆时针方向
+    pub fn cross(self, other: Point<T>) -> T {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// 距离的平方, 整数坐标下不需要开方也能比较远近
+    pub fn distance_squared(self, other: Point<T>) -> T {
+        let d = other - self;
+        d.dot(d)
+    }
+
+    /// 先转为 `f64` 再计算的距离, 避免整数坐标相减或相乘时溢出
+    pub fn distance_f64(self, other: Point<T>) -> f64 {
+        let dx = other.x.to_f64() - self.x.to_f64();
+        let dy = other.y.to_f64() - self.y.to_f64();
+        dx.hypot(dy)
+    }
+}
+
+impl<T: Float> Point<T> {
+    pub fn length(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn distance(self, other: Point<T>) -> T {
+        (other - self).length()
+    }
+
+    /// 同方向的单位向量, 零向量没有方向, 返回 `None`
+    pub fn normalized(self) -> Option<Point<T>> {
+        let length = self.length();
+        if length == T::default() {
+            None
+        } else {
+            Some(self / length)
+        }
+    }
+}
+
+impl Point<f32> {
+    pub fn distance_from_origin(&self) -> f32 {
+        (self.x.powi(2) + self.y.powi(2)).sqrt()
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+/// 数乘
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, scalar: T) -> Point<T> {
+        Point {
+            x: self.x * scalar,
+            y: self.y * scalar,
+        }
+    }
+}
+
+impl<T: Div<Output = T> + Copy> Div<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn div(self, scalar: T) -> Point<T> {
+        Point {
+            x: self.x / scalar,
+            y: self.y / scalar,
+        }
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Point<T>) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Point<T>) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T> From<[T; 2]> for Point<T> {
+    fn from([x, y]: [T; 2]) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(point: Point<T>) -> (T, T) {
+        (point.x, point.y)
+    }
+}
+
+impl<X: fmt::Display, Y: fmt::Display> fmt::Display for Point<X, Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessors_and_mixup() {
+        let p1 = Point::new(5, 10.4);
+        let p2 = Point::new("Hello", 'c');
+
+        let p3 = p1.mixup(p2);
+
+        assert_eq!((&5, &'c'), (p3.x(), p3.y()));
+        assert_eq!("(5, c)", p3.to_string());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(1, 2);
+        let b = Point::new(3, -4);
+
+        assert_eq!(Point::new(4, -2), a + b);
+        assert_eq!(Point::new(-2, 6), a - b);
+        assert_eq!(Point::new(-1, -2), -a);
+        assert_eq!(Point::new(3, 6), a * 3);
+        assert_eq!(Point::new(1.5, -2.0), Point::new(3.0, -4.0) / 2.0);
+
+        let mut c = a;
+        c += b;
+        c -= Point::new(1, 1);
+        assert_eq!(Point::new(3, -3), c);
+    }
+
+    #[test]
+    fn dot_and_cross() {
+        let x = Point::new(1, 0);
+        let y = Point::new(0, 1);
+
+        assert_eq!(0, x.dot(y));
+        assert_eq!(1, x.cross(y));
+        assert_eq!(-1, y.cross(x));
+        assert_eq!(-5, Point::new(1, 2).dot(Point::new(3, -4)));
+        assert_eq!(25, Point::new(0, 0).distance_squared(Point::new(3, 4)));
+    }
+
+    #[test]
+    fn float_distance() {
+        let a = Point::new(1.0, 1.0);
+        let b = Point::new(4.0, 5.0);
+
+        assert_eq!(5.0, a.distance(b));
+        assert_eq!(5.0, Point::new(3.0f32, 4.0).length());
+        assert_eq!(5.0, Point::new(3.0f32, 4.0).distance_from_origin());
+        assert_eq!(
+            Some(Point::new(0.6, 0.8)),
+            Point::new(3.0, 4.0).normalized()
+        );
+        assert_eq!(None, Point::new(0.0, 0.0).normalized());
+    }
+
+    #[test]
+    fn integer_distance_does_not_overflow() {
+        let a = Point::new(i32::MIN, 0);
+        let b = Point::new(i32::MAX, 0);
+
+        assert_eq!(u32::MAX as f64, a.distance_f64(b));
+    }
+
+    #[test]
+    fn conversions() {
+        let p: Point<i32> = (1, 2).into();
+        assert_eq!(p, Point::from([1, 2]));
+        assert_eq!((1, 2), p.into());
+
+        let f: Point<f64> = p.cast();
+        assert_eq!(Point::new(1.0, 2.0), f);
+
+        assert_eq!(Ok(Point::new(1i8, 2)), p.try_cast::<i8>());
+        assert!(Point::new(300, 0).try_cast::<i8>().is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use std::iter::FromIterator;
+
+use super::{Coordinate, Point};
+
+/// 依次连接各点的折线, 首尾不相连
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polyline<T> {
+    pub points: Vec<Point<T>>,
+}
+
+/// 多边形, 最后一个顶点与第一个顶点相连, 不需要重复第一个顶点
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polygon<T> {
+    pub vertices: Vec<Point<T>>,
+}
+
+impl<T: Coordinate> Polyline<T> {
+    pub fn new(points: Vec<Point<T>>) -> Polyline<T> {
+        Polyline { points }
+    }
+
+    /// 各段长度之和
+    pub fn length(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|segment| segment[0].distance_f64(segment[1]))
+            .sum()
+    }
+
+    /// 把首尾相连, 变成多边形
+    pub fn close(self) -> Polygon<T> {
+        Polygon {
+            vertices: self.points,
+        }
+    }
+}
+
+impl<T: Coordinate> Polygon<T> {
+    pub fn new(vertices: Vec<Point<T>>) -> Polygon<T> {
+        Polygon { vertices }
+    }
+
+    /// 包括首尾相连那一段在内的各条边
+    pub fn edges(&self) -> impl Iterator<Item = (Point<T>, Point<T>)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// 有向面积的两倍(鞋带公式): 顶点逆时针排列时为正
+    ///
+    /// 整数坐标的面积可能是半整数, 而两倍面积总是整数. 结果在 `T` 中累加, 超出 `T` 的范围时会溢出,
+    /// 例如 `i32` 坐标达到 33_000 左右的正方形; 只需要面积或方向时用 `area`/`is_counter_clockwise`.
+    pub fn twice_signed_area(&self) -> T {
+        self.edges()
+            .fold(T::default(), |sum, (a, b)| sum + a.cross(b))
+    }
+
+    /// 在 `f64` 中累加的两倍有向面积, 不会因为坐标类型的范围溢出
+    fn twice_signed_area_f64(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| a.x.to_f64() * b.y.to_f64() - a.y.to_f64() * b.x.to_f64())
+            .sum()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.twice_signed_area_f64().abs() / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        if self.vertices.len() < 2 {
+            return 0.0;
+        }
+        self.edges().map(|(a, b)| a.distance_f64(b)).sum()
+    }
+
+    pub fn is_counter_clockwise(&self) -> bool {
+        self.twice_signed_area_f64() > 0.0
+    }
+}
+
+impl<T> FromIterator<Point<T>> for Polyline<T> {
+    fn from_iter<I: IntoIterator<Item = Point<T>>>(iter: I) -> Polyline<T> {
+        Polyline {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> FromIterator<Point<T>> for Polygon<T> {
+    fn from_iter<I: IntoIterator<Item = Point<T>>>(iter: I) -> Polygon<T> {
+        Polygon {
+            vertices: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon<T: Copy>(points: &[(T, T)]) -> Polygon<T> {
+        points.iter().map(|&p| Point::from(p)).collect()
+    }
+
+    #[test]
+    fn integer_square() {
+        let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+
+        assert_eq!(32, square.twice_signed_area());
+        assert_eq!(16.0, square.area());
+        assert_eq!(16.0, square.perimeter());
+        assert!(square.is_counter_clockwise());
+    }
+
+    #[test]
+    fn clockwise_triangle_with_half_integer_area() {
+        let triangle = polygon(&[(0, 0), (0, 1), (1, 0)]);
+
+        assert_eq!(-1, triangle.twice_signed_area());
+        assert_eq!(0.5, triangle.area());
+        assert!(!triangle.is_counter_clockwise());
+    }
+
+    #[test]
+    fn float_triangle() {
+        let triangle = polygon(&[(0.0, 0.0), (3.0, 0.0), (0.0, 4.0)]);
+
+        assert_eq!(6.0, triangle.area());
+        assert_eq!(12.0, triangle.perimeter());
+        assert_eq!(3, triangle.edges().count());
+    }
+
+    #[test]
+    fn concave_polygon() {
+        // 缺了右上角一块的 2x2 正方形
+        let shape = polygon(&[(0, 0), (2, 0), (2, 1), (1, 1), (1, 2), (0, 2)]);
+
+        assert_eq!(3.0, shape.area());
+        assert_eq!(8.0, shape.perimeter());
+    }
+
+    #[test]
+    fn large_integer_coordinates_do_not_overflow() {
+        // 两倍面积 5_000_000_000 超出了 i32 的范围
+        let square = polygon(&[(0, 0), (50_000, 0), (50_000, 50_000), (0, 50_000)]);
+
+        assert_eq!(2_500_000_000.0, square.area());
+        assert!(square.is_counter_clockwise());
+
+        let clockwise = polygon(&[(-50_000, -50_000), (-50_000, 50_000), (50_000, -50_000)]);
+        assert_eq!(5_000_000_000.0, clockwise.area());
+        assert!(!clockwise.is_counter_clockwise());
+    }
+
+    #[test]
+    fn degenerate_polygons() {
+        assert_eq!(0.0, polygon::<i32>(&[]).area());
+        assert_eq!(0.0, polygon(&[(1, 1)]).perimeter());
+        assert_eq!(0.0, polygon(&[(0, 0), (2, 2)]).area());
+    }
+
+    #[test]
+    fn polyline_length_and_close() {
+        let line: Polyline<i64> = [(0, 0), (3, 4), (3, 0)]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+
+        assert_eq!(9.0, line.length());
+        assert_eq!(0.0, Polyline::<f64>::default().length());
+
+        let triangle = line.close();
+        assert_eq!(12.0, triangle.perimeter());
+        assert_eq!(6.0, triangle.area());
+    }
+}
@@ -0,0 +1,454 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::aggregator::{NewsArticle, Tweet};
+
+pub mod json;
+pub mod xml;
+
+#[derive(Debug, PartialEq)]
+pub enum ImportError {
+    /// XML 或 JSON 语法错误, `offset` 为输入中的字节偏移
+    Syntax { offset: usize, message: String },
+    /// 语法正确, 但不是支持的订阅源或导出格式
+    Format(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Syntax { offset, message } => {
+                write!(f, "syntax error at offset {}: {}", offset, message)
+            }
+            ImportError::Format(message) => write!(f, "unsupported format: {}", message),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+fn format_error(message: impl Into<String>) -> ImportError {
+    ImportError::Format(message.into())
+}
+
+/// 从 RSS 2.0 或 Atom 订阅源读取文章
+///
+/// 订阅源的标题作为文章的 `location`; 文章没有作者时使用订阅源的作者, 都没有时为 `unknown`.
+/// RSS 的正文和 `type` 为 `html` 的 Atom 文本是转义后的 HTML, 会去掉标签并解码实体;
+/// 其余文本原样保留, 只合并连续空白.
+pub fn articles_from_feed(input: &str) -> Result<Vec<NewsArticle>, ImportError> {
+    let root = xml::parse(input)?;
+
+    match root.name.as_str() {
+        "rss" => {
+            let channel = root
+                .child("channel")
+                .ok_or_else(|| format_error("<rss> without <channel>"))?;
+            Ok(rss_articles(channel))
+        }
+        "feed" => Ok(atom_articles(&root)),
+        other => Err(format_error(format!("unexpected root element <{}>", other))),
+    }
+}
+
+fn rss_articles(channel: &xml::Element) -> Vec<NewsArticle> {
+    let location = channel.child_text("title").unwrap_or_default();
+    let channel_author = channel.child_text("managingEditor").map(|a| rss_author(&a));
+
+    channel
+        .children_named("item")
+        .map(|item| NewsArticle {
+            headline: item.child_text("title").unwrap_or_default(),
+            location: location.clone(),
+            author: item
+                .child_text("author")
+                .map(|a| rss_author(&a))
+                .or_else(|| item.child_text("dc:creator"))
+                .or_else(|| channel_author.clone())
+                .unwrap_or_else(|| String::from("unknown")),
+            content: item
+                .child_text("content:encoded")
+                .or_else(|| item.child_text("description"))
+                .map(|html| html_text(&html))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// RSS 的作者是邮箱, 通常写成 `jane@example.com (Jane Doe)`, 有括号中的名字时取名字
+fn rss_author(author: &str) -> String {
+    match (author.find('('), author.rfind(')')) {
+        (Some(start), Some(end)) if start < end => author[start + 1..end].trim().to_string(),
+        _ => author.to_string(),
+    }
+}
+
+fn atom_articles(feed: &xml::Element) -> Vec<NewsArticle> {
+    let location = feed.child_text("title").unwrap_or_default();
+    let atom_author = |element: &xml::Element| {
+        element
+            .child("author")
+            .and_then(|author| author.child_text("name"))
+    };
+    let feed_author = atom_author(feed);
+
+    feed.children_named("entry")
+        .map(|entry| NewsArticle {
+            headline: atom_text(entry, "title").unwrap_or_default(),
+            location: location.clone(),
+            author: atom_author(entry)
+                .or_else(|| feed_author.clone())
+                .unwrap_or_else(|| String::from("unknown")),
+            content: atom_text(entry, "content")
+                .or_else(|| atom_text(entry, "summary"))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Atom 的文本: `type="html"` 时是转义后的 HTML; `type="xhtml"` 时标签已经被解析成子元素,
+/// 取文本即可; 默认的 `type="text"` 是纯文本
+fn atom_text(element: &xml::Element, name: &str) -> Option<String> {
+    let child = element.child(name)?;
+    let text = child.text();
+    let text = match child.attribute("type") {
+        Some("html") => html_text(&text),
+        _ => collapse_whitespace(&text),
+    };
+    Some(text).filter(|text| !text.is_empty())
+}
+
+/// 转义后的 HTML 转为纯文本: 先去掉标签再解码实体, 解码出的 `<` 不会被当作标签
+fn html_text(html: &str) -> String {
+    collapse_whitespace(&decode_entities(&strip_tags(html)))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 去掉 HTML 标签, 标签替换为空格
+///
+/// 只有 `<` 后面是字母、`/`、`!` 或 `?` 时才是标签, 正文中未转义的 `1 < 2` 会原样保留.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut chars = html.chars().peekable();
+    let mut in_tag = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' if !in_tag
+                && chars
+                    .peek()
+                    .is_some_and(|&next| next.is_ascii_alphabetic() || "/!?".contains(next)) =>
+            {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text
+}
+
+/// 解码 HTML 实体; 不认识的实体原样保留
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                    {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => entity.strip_prefix('#')?.parse().ok(),
+                    };
+                    code.and_then(char::from_u32)?
+                }
+            };
+            Some((c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// 从 JSON 导出读取推文
+///
+/// 整个文档必须是推文数组, 或者是带 `statuses` 数组的搜索结果; 数组中的元素可以是推文本身,
+/// 也可以是 `{"tweet": {...}}` 的包装. 存档文件开头的 JavaScript 赋值
+/// (例如 `window.YTD.tweet.part0 = `)会被跳过.
+///
+/// - 用户名: `user.screen_name` 或 `username`
+/// - 内容: `full_text` 或 `text`
+/// - 回复: `in_reply_to_status_id` 或 `in_reply_to_status_id_str` 不为空
+/// - 转推: 有 `retweeted_status`, 或者内容以 `RT @` 开头
+pub fn tweets_from_json(input: &str) -> Result<Vec<Tweet>, ImportError> {
+    let start = assignment_len(input);
+    let root = json::parse(&input[start..]).map_err(|e| match e {
+        ImportError::Syntax { offset, message } => ImportError::Syntax {
+            offset: offset + start,
+            message,
+        },
+        e => e,
+    })?;
+
+    let items = root
+        .as_array()
+        .or_else(|| root.get("statuses").and_then(json::Value::as_array))
+        .ok_or_else(|| format_error("expected an array of tweets"))?;
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| tweet(item.get("tweet").unwrap_or(item), i))
+        .collect()
+}
+
+/// 开头形如 `window.YTD.tweet.part0 =` 的赋值的字节长度, 没有时为 0
+fn assignment_len(input: &str) -> usize {
+    let target = input.trim_start();
+    let name_len = target
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "_$.".contains(c)))
+        .unwrap_or(target.len());
+    let after_name = target[name_len..].trim_start();
+
+    match after_name.strip_prefix('=') {
+        Some(value) if name_len > 0 => input.len() - value.len(),
+        _ => 0,
+    }
+}
+
+fn tweet(value: &json::Value, index: usize) -> Result<Tweet, ImportError> {
+    let field = |name: &str| value.get(name).and_then(json::Value::as_str);
+    let present = |name: &str| value.get(name).is_some_and(|v| !v.is_null());
+
+    let username = value
+        .get("user")
+        .and_then(|user| user.get("screen_name"))
+        .and_then(json::Value::as_str)
+        .or_else(|| field("username"))
+        .ok_or_else(|| format_error(format!("tweet {} has no username", index)))?;
+    let content = field("full_text")
+        .or_else(|| field("text"))
+        .ok_or_else(|| format_error(format!("tweet {} has no text", index)))?;
+
+    Ok(Tweet {
+        username: username.to_string(),
+        content: content.to_string(),
+        reply: present("in_reply_to_status_id") || present("in_reply_to_status_id_str"),
+        retweet: present("retweeted_status") || content.starts_with("RT @"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::Summary;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Pittsburgh Sports</title>
+    <managingEditor>desk@example.com (Sports Desk)</managingEditor>
+    <item>
+      <title>Penguins win the Stanley Cup Championship!</title>
+      <author>ice@example.com (Iceburgh)</author>
+      <description>&lt;p&gt;The Pittsburgh Penguins once again are the best hockey team in the NHL.&lt;/p&gt; More soon.</description>
+    </item>
+    <item>
+      <title>Parade route announced</title>
+      <dc:creator>Jane Doe</dc:creator>
+      <description><![CDATA[<b>Downtown</b>   at noon.]]></description>
+    </item>
+    <item>
+      <title>Tickets</title>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Rust 博客</title>
+  <author><name>The Rust Team</name></author>
+  <entry>
+    <title>Rust 1.0 发布</title>
+    <author><name>Ferris</name></author>
+    <summary>今天我们发布了 Rust 1.0。</summary>
+  </entry>
+  <entry>
+    <title type="html">Async &amp;amp; await</title>
+    <content type="html">&lt;p&gt;Stable at last.&lt;/p&gt;</content>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn rss_items() {
+        let articles = articles_from_feed(RSS).unwrap();
+
+        assert_eq!(3, articles.len());
+        assert_eq!(
+            "Penguins win the Stanley Cup Championship!, by Iceburgh (Pittsburgh Sports): \
+             The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            articles[0].summarize()
+        );
+        assert_eq!("Jane Doe", articles[1].author);
+        assert_eq!("Downtown at noon.", articles[1].content);
+        assert_eq!("Sports Desk", articles[2].author);
+        assert_eq!("", articles[2].content);
+    }
+
+    #[test]
+    fn atom_entries() {
+        let articles = articles_from_feed(ATOM).unwrap();
+
+        assert_eq!(2, articles.len());
+        assert_eq!(
+            "Rust 1.0 发布, by Ferris (Rust 博客): 今天我们发布了 Rust 1.0。",
+            articles[0].summarize()
+        );
+        assert_eq!("Async & await", articles[1].headline);
+        assert_eq!("The Rust Team", articles[1].author);
+        assert_eq!("Stable at last.", articles[1].content);
+    }
+
+    #[test]
+    fn only_html_text_is_unescaped() {
+        let feed = r#"<feed>
+  <entry>
+    <title>1 &lt; 2 and 3 &gt; 2</title>
+    <summary type="text">&lt;b&gt; is the bold tag &amp;amp; more</summary>
+  </entry>
+  <entry>
+    <title type="xhtml"><div>Fish <b>&amp;</b> chips</div></title>
+    <content type="html">&lt;p&gt;1 &amp;lt; 2 and 3 &amp;gt; 2&lt;/p&gt; &amp;copy; 2 &lt; 3</content>
+  </entry>
+</feed>"#;
+
+        let articles = articles_from_feed(feed).unwrap();
+
+        assert_eq!("1 < 2 and 3 > 2", articles[0].headline);
+        assert_eq!("<b> is the bold tag &amp; more", articles[0].content);
+        assert_eq!("Fish & chips", articles[1].headline);
+        // 未知实体原样保留, 没有转义的 `<` 不是标签
+        assert_eq!("1 < 2 and 3 > 2 &copy; 2 < 3", articles[1].content);
+    }
+
+    #[test]
+    fn unsupported_feeds() {
+        assert_eq!(
+            ImportError::Format(String::from("unexpected root element <html>")),
+            articles_from_feed("<html></html>").unwrap_err()
+        );
+        assert_eq!(
+            ImportError::Format(String::from("<rss> without <channel>")),
+            articles_from_feed("<rss/>").unwrap_err()
+        );
+        assert!(matches!(
+            articles_from_feed("<rss><channel></rss>"),
+            Err(ImportError::Syntax { offset: 14, .. })
+        ));
+    }
+
+    #[test]
+    fn tweets_from_api_style_export() {
+        let input = r#"[
+            {"user": {"screen_name": "horse_ebooks"}, "text": "of course, as you probably already know, people",
+             "in_reply_to_status_id": null},
+            {"user": {"screen_name": "rustlang"}, "full_text": "@ferris 是的！", "text": "ignored",
+             "in_reply_to_status_id": 123456789012345678},
+            {"user": {"screen_name": "ferris"}, "text": "RT @rustlang: Rust 1.0", "retweeted_status": {}}
+        ]"#;
+
+        let tweets = tweets_from_json(input).unwrap();
+
+        let summaries: Vec<String> = tweets.iter().map(Summary::summarize).collect();
+        assert_eq!(
+            vec![
+                "@horse_ebooks: of course, as you probably already know, people",
+                "[reply] @rustlang: @ferris 是的！",
+                "[RT] @ferris: RT @rustlang: Rust 1.0",
+            ],
+            summaries
+        );
+    }
+
+    #[test]
+    fn tweets_from_archive() {
+        let input = r#"window.YTD.tweet.part0 = [
+            {"tweet": {"username": "horse_ebooks", "full_text": "everything happens so much",
+                       "in_reply_to_status_id_str": "42"}}
+        ]"#;
+
+        let tweets = tweets_from_json(input).unwrap();
+
+        assert_eq!(1, tweets.len());
+        assert_eq!("horse_ebooks", tweets[0].username);
+        assert!(tweets[0].reply);
+        assert!(!tweets[0].retweet);
+    }
+
+    #[test]
+    fn tweet_errors() {
+        assert_eq!(
+            ImportError::Format(String::from("tweet 1 has no username")),
+            tweets_from_json(r#"[{"username": "a", "text": "x"}, {"text": "y"}]"#).unwrap_err()
+        );
+        assert_eq!(
+            ImportError::Format(String::from("expected an array of tweets")),
+            tweets_from_json("{}").unwrap_err()
+        );
+        assert_eq!(
+            "syntax error at offset 7: expected ',' or ']'",
+            tweets_from_json("x = [1 2]").unwrap_err().to_string()
+        );
+        // 只接受整个文档, 不会从中间找一个数组
+        assert_eq!(
+            "syntax error at offset 0: expected a value",
+            tweets_from_json("see [1]").unwrap_err().to_string()
+        );
+        assert_eq!(
+            ImportError::Format(String::from("expected an array of tweets")),
+            tweets_from_json(r#"{"errors": [{"username": "a", "text": "x"}]}"#).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn tweets_from_search_results() {
+        let input = r#"{"statuses": [{"user": {"screen_name": "rustlang"}, "text": "Rust 1.0"}],
+                        "search_metadata": {"count": 1}}"#;
+
+        let tweets = tweets_from_json(input).unwrap();
+
+        assert_eq!(1, tweets.len());
+        assert_eq!("rustlang", tweets[0].username);
+    }
+}
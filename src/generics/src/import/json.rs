@@ -0,0 +1,346 @@
+//! 读取导出数据所需的最小 JSON 解析器
+//!
+//! 数字统一解析为 `f64`; 对象保留键的原始顺序.
+
+use super::ImportError;
+
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// 对象中的字段, 不是对象或没有该字段时返回 `None`
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Value::Null
+    }
+}
+
+pub fn parse(input: &str) -> Result<Value, ImportError> {
+    let mut parser = Parser { input, pos: 0 };
+
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < input.len() {
+        return Err(parser.error("unexpected content after the value"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        ImportError::Syntax {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ImportError> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ImportError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Value::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) => {
+                for (word, value) in [
+                    ("null", Value::Null),
+                    ("true", Value::Bool(true)),
+                    ("false", Value::Bool(false)),
+                ] {
+                    if self.rest().starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, ImportError> {
+        self.pos += 1;
+        let mut fields = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ImportError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ImportError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(rest.len());
+
+        match rest[..len].parse() {
+            Ok(n) => {
+                self.pos += len;
+                Ok(Value::Number(n))
+            }
+            Err(_) => Err(self.error("invalid number")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ImportError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+
+        loop {
+            let Some(c) = self.peek() else {
+                self.pos = start;
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    out.push(match escape {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => self.unicode_escape()?,
+                        _ => {
+                            self.pos -= 2;
+                            return Err(self.error("invalid escape"));
+                        }
+                    });
+                }
+                c if c < ' ' => {
+                    self.pos -= 1;
+                    return Err(self.error("control character in string"));
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    /// `\u` 之后的四位十六进制数, 代理对由两个转义组成
+    fn unicode_escape(&mut self) -> Result<char, ImportError> {
+        let high = self.hex4()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("invalid \\u escape"));
+        }
+
+        if !self.rest().starts_with("\\u") {
+            return Err(self.error("unpaired surrogate"));
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error("unpaired surrogate"));
+        }
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, ImportError> {
+        let digits = self.rest().get(..4).unwrap_or("");
+        match u32::from_str_radix(digits, 16) {
+            Ok(code) if digits.len() == 4 => {
+                self.pos += 4;
+                Ok(code)
+            }
+            _ => Err(self.error("expected four hex digits")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax_error(offset: usize, message: &str) -> ImportError {
+        ImportError::Syntax {
+            offset,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn nested_values() {
+        let value = parse(r#" {"a": [1, -2.5e1, true, null], "b": {"c": "d"}, "e": []} "#).unwrap();
+
+        assert_eq!(
+            Some(
+                &[
+                    Value::Number(1.0),
+                    Value::Number(-25.0),
+                    Value::Bool(true),
+                    Value::Null
+                ][..]
+            ),
+            value.get("a").and_then(Value::as_array)
+        );
+        assert_eq!(
+            Some("d"),
+            value
+                .get("b")
+                .and_then(|b| b.get("c"))
+                .and_then(Value::as_str)
+        );
+        assert_eq!(Some(&Value::Array(vec![])), value.get("e"));
+        assert_eq!(None, value.get("missing"));
+    }
+
+    #[test]
+    fn string_escapes() {
+        let value = parse(r#""line\n\"quoted\" 中文 😀 \/""#).unwrap();
+
+        assert_eq!(Some("line\n\"quoted\" 中文 😀 /"), value.as_str());
+        assert_eq!(Some("中文"), parse("\"中文\"").unwrap().as_str());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            syntax_error(0, "unexpected end of input"),
+            parse("").unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(3, "expected ',' or ']'"),
+            parse("[1 2]").unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(1, "expected a string key"),
+            parse("{a: 1}").unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(0, "unterminated string"),
+            parse("\"abc").unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(1, "invalid escape"),
+            parse(r#""\q""#).unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(7, "unpaired surrogate"),
+            parse(r#""\ud83d""#).unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(5, "unexpected content after the value"),
+            parse("true false").unwrap_err()
+        );
+        assert_eq!(syntax_error(0, "invalid number"), parse("-").unwrap_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! 读取订阅源所需的最小 XML 解析器
+//!
+//! 支持元素、属性、文本、CDATA、预定义实体和数字字符引用; 声明、注释和 DOCTYPE 会被跳过.
+//! 不处理命名空间, 元素名保留前缀, 例如 `dc:creator`.
+
+use super::ImportError;
+
+#[derive(Debug, PartialEq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |element| element.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|element| element.name == name)
+    }
+
+    /// 所有后代文本连接起来, 去掉首尾空白
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(&mut text);
+        text.trim().to_string()
+    }
+
+    fn collect_text(&self, out: &mut String) {
+        for node in &self.children {
+            match node {
+                Node::Element(element) => element.collect_text(out),
+                Node::Text(text) => out.push_str(text),
+            }
+        }
+    }
+
+    /// 子元素的文本, 没有该子元素或文本为空时返回 `None`
+    pub fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name)
+            .map(Element::text)
+            .filter(|text| !text.is_empty())
+    }
+}
+
+/// 解析整个文档, 返回根元素
+pub fn parse(input: &str) -> Result<Element, ImportError> {
+    let mut parser = Parser { input, pos: 0 };
+
+    parser.skip_misc()?;
+    if !parser.rest().starts_with('<') {
+        return Err(parser.error("expected the root element"));
+    }
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if parser.pos < input.len() {
+        return Err(parser.error("unexpected content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        ImportError::Syntax {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// 跳到 `end` 之后, 返回跳过的内容
+    fn until(&mut self, end: &str, message: &str) -> Result<&'a str, ImportError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let skipped = &self.rest()[..i];
+                self.pos += i + end.len();
+                Ok(skipped)
+            }
+            None => Err(self.error(message)),
+        }
+    }
+
+    /// 跳过空白、声明、注释和 DOCTYPE
+    fn skip_misc(&mut self) -> Result<(), ImportError> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.until("?>", "unterminated declaration")?;
+            } else if rest.starts_with("<!--") {
+                self.until("-->", "unterminated comment")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.until(">", "unterminated DOCTYPE")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, ImportError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn element(&mut self) -> Result<Element, ImportError> {
+        self.pos += 1; // '<'
+        let name = self.name()?;
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok(Element {
+                    name,
+                    attributes,
+                    children: vec![],
+                });
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            if rest.is_empty() {
+                return Err(self.error("unterminated start tag"));
+            }
+            attributes.push(self.attribute()?);
+        }
+
+        let children = self.content(&name)?;
+        Ok(Element {
+            name,
+            attributes,
+            children,
+        })
+    }
+
+    fn attribute(&mut self) -> Result<(String, String), ImportError> {
+        let name = self.name()?;
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            return Err(self.error("expected '=' after attribute name"));
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("expected a quoted attribute value")),
+        };
+        let start = self.pos;
+        self.pos += 1;
+        let raw = self.until(&quote.to_string(), "unterminated attribute value")?;
+        Ok((name, decode(raw, start + 1)?))
+    }
+
+    /// 解析到与 `name` 对应的结束标签为止
+    fn content(&mut self, name: &str) -> Result<Vec<Node>, ImportError> {
+        let mut children = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!("missing </{}>", name)));
+            }
+
+            if rest.starts_with("</") {
+                let start = self.pos;
+                self.pos += 2;
+                let closing = self.name()?;
+                self.skip_whitespace();
+                if closing != name || !self.rest().starts_with('>') {
+                    self.pos = start;
+                    return Err(self.error(&format!("expected </{}>", name)));
+                }
+                self.pos += 1;
+                break;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                text.push_str(self.until("]]>", "unterminated CDATA section")?);
+            } else if rest.starts_with("<!--") {
+                self.until("-->", "unterminated comment")?;
+            } else if rest.starts_with("<?") {
+                self.until("?>", "unterminated processing instruction")?;
+            } else if rest.starts_with('<') {
+                flush(&mut text, &mut children);
+                children.push(Node::Element(self.element()?));
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                text.push_str(&decode(&rest[..len], self.pos)?);
+                self.pos += len;
+            }
+        }
+
+        flush(&mut text, &mut children);
+        Ok(children)
+    }
+}
+
+fn flush(text: &mut String, children: &mut Vec<Node>) {
+    if !text.is_empty() {
+        children.push(Node::Text(std::mem::take(text)));
+    }
+}
+
+/// 替换实体和字符引用, `offset` 用于报告错误位置
+fn decode(raw: &str, offset: usize) -> Result<String, ImportError> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        let entity_start = offset + raw.len() - rest.len() + i;
+        let error = |message: &str| ImportError::Syntax {
+            offset: entity_start,
+            message: message.to_string(),
+        };
+
+        let end = rest[i..]
+            .find(';')
+            .ok_or_else(|| error("unterminated entity"))?;
+        let entity = &rest[i + 1..i + end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    return Err(error(&format!("unknown entity &{};", entity)));
+                };
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| error("invalid character reference"))?
+            }
+        };
+        out.push(c);
+        rest = &rest[i + end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax_error(offset: usize, message: &str) -> ImportError {
+        ImportError::Syntax {
+            offset,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn elements_attributes_and_text() {
+        let root = parse(
+            r#"<?xml version="1.0"?>
+<!-- 注释 -->
+<feed lang='zh'>
+  <title type="text">Tom &amp; Jerry &#x4E2D;&#25991;</title>
+  <link href="https://example.com/?a=1&amp;b=2"/>
+  <summary><![CDATA[<b>bold</b> & raw]]></summary>
+</feed>"#,
+        )
+        .unwrap();
+
+        assert_eq!("feed", root.name);
+        assert_eq!(Some("zh"), root.attribute("lang"));
+        assert_eq!(3, root.elements().count());
+        assert_eq!("Tom & Jerry 中文", root.child("title").unwrap().text());
+        assert_eq!(
+            Some("https://example.com/?a=1&b=2"),
+            root.child("link").unwrap().attribute("href")
+        );
+        assert_eq!(
+            Some("<b>bold</b> & raw".to_string()),
+            root.child_text("summary")
+        );
+        assert_eq!(None, root.child_text("link"));
+    }
+
+    #[test]
+    fn text_includes_descendants() {
+        let root = parse("<p>a <b>bold</b> move</p>").unwrap();
+
+        assert_eq!("a bold move", root.text());
+        assert_eq!(3, root.children.len());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            syntax_error(0, "expected the root element"),
+            parse("text").unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(6, "expected </b>"),
+            parse("<a><b></a>").unwrap_err()
+        );
+        assert_eq!(syntax_error(3, "missing </a>"), parse("<a>").unwrap_err());
+        assert_eq!(
+            syntax_error(3, "unknown entity &nbsp;"),
+            parse("<a>&nbsp;</a>").unwrap_err()
+        );
+        assert_eq!(
+            syntax_error(7, "unexpected content after the root element"),
+            parse("<a></a><b/>").unwrap_err()
+        );
+    }
+}
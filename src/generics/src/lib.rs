@@ -1 +1,9 @@
 pub mod aggregator;
+pub mod feed;
+pub mod geometry;
+pub mod import;
+pub mod render;
+pub mod search;
+pub mod select;
+pub mod similarity;
+pub mod text;
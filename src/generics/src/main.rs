@@ -1,7 +1,10 @@
-use crate::aggregator::{Summary, Tweet};
+use std::env;
+use std::process;
+
 use crate::back::main2;
+use generics::aggregator::{Summary, Tweet};
+use generics::render::{HtmlRenderer, MarkdownRenderer, Renderer, TextRenderer};
 
-pub mod aggregator;
 pub mod back;
 
 fn main() {
@@ -12,6 +15,24 @@ fn main() {
         retweet: false,
     };
 
+    // 传入格式名时输出对应格式的摘要, 例如 `cargo run -- markdown`
+    if let Some(format) = env::args().nth(1) {
+        let renderer: Box<dyn Renderer> = match format.as_str() {
+            "html" => Box::new(HtmlRenderer),
+            "markdown" => Box::new(MarkdownRenderer),
+            "text" => Box::new(TextRenderer::default()),
+            _ => {
+                eprintln!(
+                    "unknown format: {} (expected html, markdown or text)",
+                    format
+                );
+                process::exit(1);
+            }
+        };
+        print!("{}", renderer.render("1 new tweet", &[&tweet]));
+        return;
+    }
+
     println!("1 new tweet: {}", tweet.summarize());
 
     main2();
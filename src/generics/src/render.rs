@@ -0,0 +1,31 @@
+use crate::aggregator::Summary;
+
+pub use html::HtmlRenderer;
+pub use markdown::MarkdownRenderer;
+pub use text::TextRenderer;
+
+pub mod html;
+pub mod markdown;
+pub mod text;
+
+/// 把一组内容渲染成完整的文档
+///
+/// 实现者只需要给出开头、每一条内容和结尾的写法, `render` 负责把它们拼起来.
+pub trait Renderer {
+    fn begin(&self, out: &mut String, title: &str, count: usize);
+
+    /// `index` 从 0 开始
+    fn item(&self, out: &mut String, index: usize, item: &dyn Summary);
+
+    fn end(&self, out: &mut String, count: usize);
+
+    fn render(&self, title: &str, items: &[&dyn Summary]) -> String {
+        let mut out = String::new();
+        self.begin(&mut out, title, items.len());
+        for (index, item) in items.iter().enumerate() {
+            self.item(&mut out, index, *item);
+        }
+        self.end(&mut out, items.len());
+        out
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Write;
+
+use super::Renderer;
+use crate::aggregator::Summary;
+
+/// 生成一个独立的 HTML 页面, 内容为无序列表
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn begin(&self, out: &mut String, title: &str, count: usize) {
+        let title = escape(title);
+        write!(
+            out,
+            "<!DOCTYPE html>\n\
+             <html>\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n\
+             </head>\n\
+             <body>\n\
+             <h1>{title}</h1>\n"
+        )
+        .unwrap();
+
+        out.push_str(if count == 0 {
+            "<p>Nothing new.</p>\n"
+        } else {
+            "<ul>\n"
+        });
+    }
+
+    fn item(&self, out: &mut String, _index: usize, item: &dyn Summary) {
+        writeln!(
+            out,
+            "<li><strong>{}</strong> {}</li>",
+            escape(&item.summarize_author()),
+            escape(&item.summarize_body())
+        )
+        .unwrap();
+    }
+
+    fn end(&self, out: &mut String, count: usize) {
+        if count > 0 {
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;",
+            escape("<a href=\"x\">Tom & Jerry's</a>")
+        );
+    }
+
+    #[test]
+    fn empty_page() {
+        let html = HtmlRenderer.render("Weekly", &[]);
+
+        assert!(html.contains("<p>Nothing new.</p>\n</body>"));
+        assert!(!html.contains("<ul>"));
+    }
+}
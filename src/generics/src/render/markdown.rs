@@ -0,0 +1,61 @@
+use std::fmt::Write;
+
+use super::Renderer;
+use crate::aggregator::Summary;
+
+/// 生成 Markdown 摘要: 一级标题加上每条内容一个列表项
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn begin(&self, out: &mut String, title: &str, count: usize) {
+        writeln!(out, "# {}\n", escape(title)).unwrap();
+        if count == 0 {
+            out.push_str("_Nothing new._\n");
+        }
+    }
+
+    fn item(&self, out: &mut String, _index: usize, item: &dyn Summary) {
+        writeln!(
+            out,
+            "- **{}**: {}",
+            escape(&item.summarize_author()),
+            escape(&item.summarize_body())
+        )
+        .unwrap();
+    }
+
+    fn end(&self, _out: &mut String, _count: usize) {}
+}
+
+/// 转义会被当作 Markdown 语法的字符
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_syntax() {
+        assert_eq!(
+            "\\*bold\\* \\_it\\_ \\[link\\] \\#1 \\`code\\`",
+            escape("*bold* _it_ [link] #1 `code`")
+        );
+    }
+
+    #[test]
+    fn empty_digest() {
+        assert_eq!(
+            "# Weekly\n\n_Nothing new._\n",
+            MarkdownRenderer.render("Weekly", &[])
+        );
+    }
+}
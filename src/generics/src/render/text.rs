@@ -0,0 +1,120 @@
+use std::fmt::Write;
+
+use super::Renderer;
+use crate::aggregator::Summary;
+use crate::text::{display_width, graphemes};
+
+/// 生成纯文本邮件正文, 每行不超过 `width` 列
+pub struct TextRenderer {
+    pub width: usize,
+}
+
+const INDENT: &str = "   ";
+
+impl Default for TextRenderer {
+    fn default() -> TextRenderer {
+        TextRenderer { width: 72 }
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn begin(&self, out: &mut String, title: &str, count: usize) {
+        writeln!(out, "{}\n{}\n", title, "=".repeat(display_width(title))).unwrap();
+        if count == 0 {
+            out.push_str("Nothing new.\n\n");
+        }
+    }
+
+    fn item(&self, out: &mut String, index: usize, item: &dyn Summary) {
+        writeln!(
+            out,
+            "{:<3}{}",
+            format!("{}.", index + 1),
+            item.summarize_author()
+        )
+        .unwrap();
+        let width = self.width.saturating_sub(INDENT.len()).max(1);
+        for line in wrap(&item.summarize_body(), width) {
+            writeln!(out, "{}{}", INDENT, line).unwrap();
+        }
+        out.push('\n');
+    }
+
+    fn end(&self, out: &mut String, count: usize) {
+        let noun = if count == 1 { "item" } else { "items" };
+        writeln!(out, "-- \n{} {}", count, noun).unwrap();
+    }
+}
+
+/// 按单词折行, 每行不超过 `width` 列; 比一整行还长的单词(包括没有空格的中日韩文本)按字素断开
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = display_width(word);
+
+        if line_width > 0 && line_width + 1 + word_width <= width {
+            line.push(' ');
+            line.push_str(word);
+            line_width += 1 + word_width;
+            continue;
+        }
+
+        if line_width > 0 {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+
+        if word_width <= width {
+            line.push_str(word);
+            line_width = word_width;
+            continue;
+        }
+
+        for cluster in graphemes(word) {
+            let cluster_width = display_width(cluster);
+            if line_width > 0 && line_width + cluster_width > width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            line.push_str(cluster);
+            line_width += cluster_width;
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_on_words() {
+        assert_eq!(
+            vec!["the quick", "brown fox", "jumps"],
+            wrap("the quick brown   fox jumps", 10)
+        );
+        assert!(wrap("", 10).is_empty());
+    }
+
+    #[test]
+    fn wrap_long_words_and_cjk() {
+        assert_eq!(vec!["abcd", "efgh", "ij"], wrap("abcdefghij", 4));
+        assert_eq!(vec!["企鹅队", "再次夺", "冠"], wrap("企鹅队再次夺冠", 6));
+        assert_eq!(vec!["a", "企鹅"], wrap("a 企鹅", 4));
+    }
+
+    #[test]
+    fn empty_digest() {
+        assert_eq!(
+            "Weekly\n======\n\nNothing new.\n\n-- \n0 items\n",
+            TextRenderer::default().render("Weekly", &[])
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! 内存中的倒排索引, 支持 AND/OR/短语查询, 按 BM25 排序
+//!
+//! 索引只保存对原始内容的引用, 搜索结果可以直接交给 `Renderer` 渲染.
+
+use std::collections::{BTreeSet, HashMap};
+use std::iter::FromIterator;
+
+use crate::aggregator::Summary;
+use crate::similarity::tokens;
+
+pub use query::{Query, QueryError};
+
+pub mod query;
+
+/// BM25 中词频的饱和参数
+const K1: f64 = 1.2;
+/// BM25 中按文档长度归一化的程度
+const B: f64 = 0.75;
+
+/// 一条搜索结果
+#[derive(Clone, Copy)]
+pub struct Hit<'a> {
+    pub item: &'a dyn Summary,
+    pub score: f64,
+}
+
+/// 某个词在某条内容中出现的位置
+struct Posting {
+    doc: usize,
+    positions: Vec<usize>,
+}
+
+/// 按 `Summary::search_text` 建立的倒排索引: 文章是标题和正文, 推文是正文
+#[derive(Default)]
+pub struct Index<'a> {
+    items: Vec<&'a dyn Summary>,
+    lengths: Vec<usize>,
+    total_length: usize,
+    /// 每个词的倒排列表, 按内容加入的顺序排列
+    postings: HashMap<String, Vec<Posting>>,
+}
+
+impl<'a> Index<'a> {
+    pub fn new() -> Index<'a> {
+        Index::default()
+    }
+
+    pub fn add(&mut self, item: &'a dyn Summary) {
+        let doc = self.items.len();
+        let words = tokens(&item.search_text());
+
+        for (position, word) in words.iter().enumerate() {
+            let postings = self.postings.entry(word.clone()).or_default();
+            match postings.last_mut() {
+                Some(posting) if posting.doc == doc => posting.positions.push(position),
+                _ => postings.push(Posting {
+                    doc,
+                    positions: vec![position],
+                }),
+            }
+        }
+
+        self.items.push(item);
+        self.lengths.push(words.len());
+        self.total_length += words.len();
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 解析并执行查询, 结果按得分从高到低排列, 得分相同时先加入的在前
+    pub fn search(&self, query: &str) -> Result<Vec<Hit<'a>>, QueryError> {
+        Ok(self.search_query(&Query::parse(query)?))
+    }
+
+    pub fn search_query(&self, query: &Query) -> Vec<Hit<'a>> {
+        let terms = query.terms();
+        let mut hits: Vec<(usize, f64)> = self
+            .matching(query)
+            .into_iter()
+            .map(|doc| (doc, self.score(doc, &terms)))
+            .collect();
+
+        // 匹配结果按下标升序, 稳定排序后得分相同的保持加入顺序
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+        hits.into_iter()
+            .map(|(doc, score)| Hit {
+                item: self.items[doc],
+                score,
+            })
+            .collect()
+    }
+
+    /// 满足查询条件的内容的下标, 不计算得分
+    fn matching(&self, query: &Query) -> BTreeSet<usize> {
+        match query {
+            Query::Term(term) => self.docs(term).collect(),
+            Query::Phrase(words) => {
+                let (first, rest) = match words.split_first() {
+                    Some(split) => split,
+                    None => return BTreeSet::new(),
+                };
+                self.docs(first)
+                    .filter(|&doc| self.contains_phrase(doc, first, rest))
+                    .collect()
+            }
+            Query::And(queries) => {
+                let mut sets = queries.iter().map(|query| self.matching(query));
+                let first = sets.next().unwrap_or_default();
+                sets.fold(first, |all, set| &all & &set)
+            }
+            Query::Or(queries) => queries
+                .iter()
+                .flat_map(|query| self.matching(query))
+                .collect(),
+        }
+    }
+
+    fn docs<'s>(&'s self, term: &str) -> impl Iterator<Item = usize> + 's {
+        self.postings
+            .get(term)
+            .into_iter()
+            .flatten()
+            .map(|posting| posting.doc)
+    }
+
+    fn posting(&self, term: &str, doc: usize) -> Option<&Posting> {
+        let postings = self.postings.get(term)?;
+        let index = postings
+            .binary_search_by_key(&doc, |posting| posting.doc)
+            .ok()?;
+        Some(&postings[index])
+    }
+
+    /// `first` 出现的某个位置之后紧接着依次是 `rest` 中的词
+    fn contains_phrase(&self, doc: usize, first: &str, rest: &[String]) -> bool {
+        let following: Option<Vec<&Posting>> =
+            rest.iter().map(|word| self.posting(word, doc)).collect();
+        let (start, following) = match (self.posting(first, doc), following) {
+            (Some(start), Some(following)) => (start, following),
+            _ => return false,
+        };
+
+        start.positions.iter().any(|&position| {
+            following.iter().enumerate().all(|(offset, posting)| {
+                posting
+                    .positions
+                    .binary_search(&(position + offset + 1))
+                    .is_ok()
+            })
+        })
+    }
+
+    /// BM25 得分; OR 查询中没有出现的词不计分
+    fn score(&self, doc: usize, terms: &[&str]) -> f64 {
+        let count = self.items.len() as f64;
+        let average_length = self.total_length as f64 / count;
+        let length = self.lengths[doc] as f64;
+
+        terms
+            .iter()
+            .filter_map(|&term| {
+                let frequency = self.posting(term, doc)?.positions.len() as f64;
+                let documents = self.postings[term].len() as f64;
+                let idf = (1.0 + (count - documents + 0.5) / (documents + 0.5)).ln();
+                let norm = K1 * (1.0 - B + B * length / average_length);
+                Some(idf * frequency * (K1 + 1.0) / (frequency + norm))
+            })
+            .sum()
+    }
+}
+
+impl<'a> FromIterator<&'a dyn Summary> for Index<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a dyn Summary>>(iter: I) -> Index<'a> {
+        let mut index = Index::new();
+        for item in iter {
+            index.add(item);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::{NewsArticle, Tweet};
+    use crate::render::{MarkdownRenderer, Renderer};
+
+    fn tweet(username: &str, content: &str) -> Tweet {
+        Tweet {
+            username: String::from(username),
+            content: String::from(content),
+            reply: false,
+            retweet: false,
+        }
+    }
+
+    fn article(author: &str, headline: &str, content: &str) -> NewsArticle {
+        NewsArticle {
+            headline: String::from(headline),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from(author),
+            content: String::from(content),
+        }
+    }
+
+    struct Sample {
+        penguins: NewsArticle,
+        hockey: NewsArticle,
+        rust: Tweet,
+        cup: Tweet,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            penguins: article(
+                "Iceburgh",
+                "Penguins win the Stanley Cup Championship!",
+                "The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            ),
+            hockey: article(
+                "sports_desk",
+                "Hockey season preview",
+                "Which team will lift the cup? The Stanley Cup playoffs start soon.",
+            ),
+            rust: tweet(
+                "rustlang",
+                "Rust 1.0 is here! Ownership without a garbage collector.",
+            ),
+            cup: tweet("horse_ebooks", "a cup of coffee and some rust, stanley"),
+        }
+    }
+
+    fn authors(hits: &[Hit]) -> Vec<String> {
+        hits.iter().map(|hit| hit.item.summarize_author()).collect()
+    }
+
+    fn search(query: &str) -> Vec<String> {
+        let sample = sample();
+        let index: Index = [
+            &sample.penguins as &dyn Summary,
+            &sample.hockey,
+            &sample.rust,
+            &sample.cup,
+        ]
+        .into_iter()
+        .collect();
+        authors(&index.search(query).unwrap())
+    }
+
+    #[test]
+    fn headlines_are_indexed() {
+        assert_eq!(vec!["@Iceburgh"], search("championship"));
+        assert_eq!(vec!["@sports_desk"], search("preview"));
+    }
+
+    #[test]
+    fn and_requires_every_term() {
+        assert_eq!(vec!["@horse_ebooks"], search("rust cup"));
+        assert_eq!(vec!["@horse_ebooks"], search("Rust AND coffee"));
+        assert!(search("rust hockey").is_empty());
+        assert!(search("nonexistent").is_empty());
+    }
+
+    #[test]
+    fn or_matches_any_term() {
+        let hits = search("coffee OR ownership");
+        assert_eq!(2, hits.len());
+        assert!(hits.contains(&String::from("@rustlang")));
+
+        let mut hits = search("(championship OR preview) stanley");
+        hits.sort();
+        assert_eq!(vec!["@Iceburgh", "@sports_desk"], hits);
+    }
+
+    #[test]
+    fn phrases_must_be_consecutive() {
+        // 推文中 cup 和 stanley 都出现了, 但不相邻
+        let mut hits = search("\"stanley cup\"");
+        hits.sort();
+        assert_eq!(vec!["@Iceburgh", "@sports_desk"], hits);
+
+        assert_eq!(vec!["@rustlang"], search("1.0"));
+        assert!(search("\"cup stanley\"").is_empty());
+    }
+
+    #[test]
+    fn bm25_ranks_frequent_and_rare_terms_higher() {
+        // 两次提到 cup 且较短的预览排在前面
+        assert_eq!(
+            vec!["@sports_desk", "@horse_ebooks", "@Iceburgh"],
+            search("cup")
+        );
+
+        let sample = sample();
+        let index: Index = [&sample.cup as &dyn Summary, &sample.hockey]
+            .into_iter()
+            .collect();
+        let hits = index.search("cup OR coffee").unwrap();
+        // coffee 只在一条内容中出现, 权重更高
+        assert_eq!(vec!["@horse_ebooks", "@sports_desk"], authors(&hits));
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn ties_keep_insertion_order() {
+        let a = tweet("a", "same words");
+        let b = tweet("b", "same words");
+        let index: Index = [&a as &dyn Summary, &b].into_iter().collect();
+
+        assert_eq!(vec!["@a", "@b"], authors(&index.search("words").unwrap()));
+    }
+
+    #[test]
+    fn empty_index_and_bad_queries() {
+        let index = Index::new();
+
+        assert!(index.is_empty());
+        assert!(index.search("rust").unwrap().is_empty());
+        assert_eq!(
+            "at offset 0: unterminated phrase",
+            index.search("\"rust").err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn hits_can_be_rendered() {
+        let sample = sample();
+        let mut index = Index::new();
+        index.add(&sample.rust);
+        index.add(&sample.cup);
+        assert_eq!(2, index.len());
+
+        let items: Vec<&dyn Summary> = index
+            .search("rust")
+            .unwrap()
+            .iter()
+            .map(|hit| hit.item)
+            .collect();
+        let digest = MarkdownRenderer.render("Search: rust", &items);
+
+        assert!(digest.starts_with("# Search: rust\n"));
+        assert!(digest.contains("**@rustlang**"));
+        assert!(digest.contains("**@horse\\_ebooks**"));
+    }
+}
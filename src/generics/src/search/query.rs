@@ -0,0 +1,336 @@
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+use crate::similarity::tokens;
+
+/// 解析后的查询
+///
+/// 语法: 用空格分隔的多个条件表示 AND, 也可以显式写 `AND`; `OR` 的优先级低于 AND;
+/// 双引号括起来的是短语; 圆括号用来分组. `AND` 和 `OR` 必须大写, 小写时当作普通的词.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Term(String),
+    /// 按顺序紧挨着出现的多个词
+    Phrase(Vec<String>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// 出错位置在查询中的字节偏移
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at offset {}: {}", self.offset, self.message)
+    }
+}
+
+impl Error for QueryError {}
+
+fn error(offset: usize, message: impl Into<String>) -> QueryError {
+    QueryError {
+        offset,
+        message: message.into(),
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let mut lexer = Lexer {
+            chars: input.char_indices().peekable(),
+            end: input.len(),
+            peeked: None,
+        };
+
+        let query = parse_or(&mut lexer)?;
+        match lexer.next()? {
+            (_, Token::End) => Ok(query),
+            (offset, Token::Close) => Err(error(offset, "unmatched ')'")),
+            (offset, _) => Err(error(offset, "unexpected input")),
+        }
+    }
+
+    /// 查询中出现的所有词(包括短语中的词), 去重并保持出现顺序
+    pub fn terms(&self) -> Vec<&str> {
+        let mut terms = Vec::new();
+        self.collect_terms(&mut terms);
+        terms
+    }
+
+    fn collect_terms<'a>(&'a self, terms: &mut Vec<&'a str>) {
+        let mut add = |term: &'a String| {
+            if !terms.contains(&term.as_str()) {
+                terms.push(term);
+            }
+        };
+
+        match self {
+            Query::Term(term) => add(term),
+            Query::Phrase(words) => words.iter().for_each(add),
+            Query::And(queries) | Query::Or(queries) => {
+                for query in queries {
+                    query.collect_terms(terms);
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(input: &str) -> Result<Query, QueryError> {
+        Query::parse(input)
+    }
+}
+
+fn parse_or(lexer: &mut Lexer) -> Result<Query, QueryError> {
+    let mut queries = vec![parse_and(lexer)?];
+    while let (_, Token::Or) = lexer.peek()? {
+        lexer.next()?;
+        queries.push(parse_and(lexer)?);
+    }
+    Ok(combine(queries, Query::Or))
+}
+
+fn parse_and(lexer: &mut Lexer) -> Result<Query, QueryError> {
+    let mut queries = vec![parse_primary(lexer)?];
+    loop {
+        match lexer.peek()? {
+            (_, Token::And) => {
+                lexer.next()?;
+            }
+            (_, Token::Word(_) | Token::Quoted(_) | Token::Open) => {}
+            _ => break,
+        }
+        queries.push(parse_primary(lexer)?);
+    }
+    Ok(combine(queries, Query::And))
+}
+
+fn parse_primary(lexer: &mut Lexer) -> Result<Query, QueryError> {
+    match lexer.next()? {
+        (offset, Token::Word(word)) => words(offset, &word),
+        (offset, Token::Quoted(phrase)) => match words(offset, &phrase)? {
+            Query::Term(term) => Ok(Query::Term(term)),
+            phrase => Ok(phrase),
+        },
+        (offset, Token::Open) => {
+            let query = parse_or(lexer)?;
+            match lexer.next()? {
+                (_, Token::Close) => Ok(query),
+                _ => Err(error(offset, "unmatched '('")),
+            }
+        }
+        (offset, Token::End) => Err(error(offset, "expected a word, phrase or '('")),
+        (offset, token) => Err(error(offset, format!("unexpected {}", token))),
+    }
+}
+
+/// 与索引使用相同的切分规则; 一个词切出多个词时(例如 `1.0` 或中文)按短语处理
+fn words(offset: usize, text: &str) -> Result<Query, QueryError> {
+    let mut words = tokens(text);
+    match words.len() {
+        0 => Err(error(
+            offset,
+            format!("{:?} has no searchable characters", text),
+        )),
+        1 => Ok(Query::Term(words.remove(0))),
+        _ => Ok(Query::Phrase(words)),
+    }
+}
+
+/// 只有一个条件时不需要 AND/OR 包装
+fn combine(mut queries: Vec<Query>, op: fn(Vec<Query>) -> Query) -> Query {
+    if queries.len() == 1 {
+        queries.remove(0)
+    } else {
+        op(queries)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    And,
+    Or,
+    Open,
+    Close,
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "{:?}", word),
+            Token::Quoted(phrase) => write!(f, "\"{}\"", phrase),
+            Token::And => f.write_str("AND"),
+            Token::Or => f.write_str("OR"),
+            Token::Open => f.write_str("'('"),
+            Token::Close => f.write_str("')'"),
+            Token::End => f.write_str("end of query"),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    end: usize,
+    peeked: Option<(usize, Token)>,
+}
+
+impl Lexer<'_> {
+    fn peek(&mut self) -> Result<&(usize, Token), QueryError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read()?);
+        }
+        Ok(self.peeked.as_ref().unwrap())
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), QueryError> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.read(),
+        }
+    }
+
+    fn read(&mut self) -> Result<(usize, Token), QueryError> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+        let (start, c) = match self.chars.next() {
+            Some(next) => next,
+            None => return Ok((self.end, Token::End)),
+        };
+
+        let token = match c {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '"' => {
+                let mut phrase = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => phrase.push(c),
+                        None => return Err(error(start, "unterminated phrase")),
+                    }
+                }
+                Token::Quoted(phrase)
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = self
+                    .chars
+                    .next_if(|&(_, c)| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))
+                {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    _ => Token::Word(word),
+                }
+            }
+        };
+        Ok((start, token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(word: &str) -> Query {
+        Query::Term(String::from(word))
+    }
+
+    fn phrase(words: &[&str]) -> Query {
+        Query::Phrase(words.iter().map(|&word| String::from(word)).collect())
+    }
+
+    fn parse(input: &str) -> Query {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn implicit_and_explicit_and() {
+        let expected = Query::And(vec![term("rust"), term("release")]);
+
+        assert_eq!(expected, parse("Rust release"));
+        assert_eq!(expected, parse("rust AND release"));
+        assert_eq!(term("rust"), parse("  rust  "));
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        assert_eq!(
+            Query::Or(vec![
+                Query::And(vec![term("a"), term("b")]),
+                term("c"),
+                term("d")
+            ]),
+            parse("a b OR c OR d")
+        );
+        assert_eq!(
+            Query::And(vec![term("a"), Query::Or(vec![term("b"), term("c")])]),
+            parse("a (b OR c)")
+        );
+    }
+
+    #[test]
+    fn lowercase_operators_are_words() {
+        assert_eq!(
+            Query::And(vec![term("cats"), term("and"), term("dogs")]),
+            parse("cats and dogs")
+        );
+    }
+
+    #[test]
+    fn phrases() {
+        assert_eq!(phrase(&["stanley", "cup"]), parse("\"Stanley Cup\""));
+        assert_eq!(term("cup"), parse("\"cup!\""));
+        // 会被切成多个词的词按短语处理
+        assert_eq!(phrase(&["1", "0"]), parse("1.0"));
+        assert_eq!(phrase(&["所", "有", "权"]), parse("所有权"));
+    }
+
+    #[test]
+    fn terms_are_deduplicated() {
+        assert_eq!(
+            vec!["rust", "cup", "stanley"],
+            parse("rust (cup OR \"stanley cup\" OR rust)").terms()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            ("", 0, "expected a word, phrase or '('"),
+            ("rust OR", 7, "expected a word, phrase or '('"),
+            ("AND rust", 0, "unexpected AND"),
+            ("(rust", 0, "unmatched '('"),
+            ("rust)", 4, "unmatched ')'"),
+            ("\"stanley cup", 0, "unterminated phrase"),
+            ("rust !!!", 5, "\"!!!\" has no searchable characters"),
+        ];
+
+        for (input, offset, message) in cases {
+            assert_eq!(
+                Err(error(offset, message)),
+                Query::parse(input),
+                "{:?}",
+                input
+            );
+        }
+        assert_eq!(
+            "at offset 4: unmatched ')'",
+            Query::parse("rust)").unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! 泛型的选择算法: 最大、最小、前 k 个、第 k 大和中位数
+//!
+//! 与 `back::largest` 不同, 这里的函数在空切片上返回 `None` 而不是 panic, 并且不会修改输入.
+//! 有多个相等的元素时, 总是返回在切片中最先出现的那个.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+pub fn largest<T: PartialOrd>(list: &[T]) -> Option<&T> {
+    largest_by_key(list, |item| item)
+}
+
+pub fn smallest<T: PartialOrd>(list: &[T]) -> Option<&T> {
+    smallest_by_key(list, |item| item)
+}
+
+pub fn largest_by_key<'a, T, K, F>(list: &'a [T], mut key: F) -> Option<&'a T>
+where
+    K: PartialOrd,
+    F: FnMut(&'a T) -> K,
+{
+    let mut items = list.iter();
+    let mut best = items.next()?;
+    let mut best_key = key(best);
+
+    for item in items {
+        let item_key = key(item);
+        if item_key > best_key {
+            best = item;
+            best_key = item_key;
+        }
+    }
+    Some(best)
+}
+
+pub fn smallest_by_key<'a, T, K, F>(list: &'a [T], mut key: F) -> Option<&'a T>
+where
+    K: PartialOrd,
+    F: FnMut(&'a T) -> K,
+{
+    let mut items = list.iter();
+    let mut best = items.next()?;
+    let mut best_key = key(best);
+
+    for item in items {
+        let item_key = key(item);
+        if item_key < best_key {
+            best = item;
+            best_key = item_key;
+        }
+    }
+    Some(best)
+}
+
+/// 一次遍历同时找出最小值和最大值
+pub fn min_max<T: PartialOrd>(list: &[T]) -> Option<(&T, &T)> {
+    min_max_by_key(list, |item| item)
+}
+
+/// 成对比较, 大约只需要 3n/2 次比较
+pub fn min_max_by_key<'a, T, K, F>(list: &'a [T], mut key: F) -> Option<(&'a T, &'a T)>
+where
+    K: PartialOrd,
+    F: FnMut(&'a T) -> K,
+{
+    let (first, rest) = list.split_first()?;
+    let (mut min, mut max) = (first, first);
+    let (mut min_key, mut max_key) = (key(first), key(first));
+
+    let mut pairs = rest.chunks(2);
+    for pair in &mut pairs {
+        // 两个元素中较小的只需和最小值比较, 较大的只需和最大值比较
+        let (small, small_key, large, large_key) = match pair {
+            [a, b] => {
+                let (a_key, b_key) = (key(a), key(b));
+                if b_key < a_key {
+                    (b, b_key, a, a_key)
+                } else if a_key < b_key {
+                    (a, a_key, b, b_key)
+                } else {
+                    // 相等时两边都取先出现的 a
+                    (a, a_key, a, b_key)
+                }
+            }
+            [a] => (a, key(a), a, key(a)),
+            _ => unreachable!(),
+        };
+
+        if small_key < min_key {
+            min = small;
+            min_key = small_key;
+        }
+        if large_key > max_key {
+            max = large;
+            max_key = large_key;
+        }
+    }
+    Some((min, max))
+}
+
+/// 最大的 `k` 个元素, 从大到小排列; 用大小为 `k` 的最小堆, 复杂度 O(n log k)
+pub fn top_k<T: Ord>(list: &[T], k: usize) -> Vec<&T> {
+    top_k_by_key(list, k, |item| item)
+}
+
+pub fn top_k_by_key<'a, T, K, F>(list: &'a [T], k: usize, mut key: F) -> Vec<&'a T>
+where
+    K: Ord,
+    F: FnMut(&'a T) -> K,
+{
+    if k == 0 {
+        return vec![];
+    }
+
+    // 键相同时下标小的更优先保留, 因此堆中按 (键, 反向下标) 比较
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (index, item) in list.iter().enumerate() {
+        heap.push(Reverse((key(item), Reverse(index))));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((_, Reverse(index)))| &list[index])
+        .collect()
+}
+
+/// 第 `k` 大的元素, `k` 从 1 开始: `kth_largest(list, 1)` 就是最大值
+///
+/// 与 `top_k(list, k)` 的最后一个元素相同. 使用快速选择, 平均复杂度 O(n).
+pub fn kth_largest<T: Ord>(list: &[T], k: usize) -> Option<&T> {
+    kth_largest_by_key(list, k, |item| item)
+}
+
+pub fn kth_largest_by_key<'a, T, K, F>(list: &'a [T], k: usize, mut key: F) -> Option<&'a T>
+where
+    K: Ord,
+    F: FnMut(&'a T) -> K,
+{
+    if k == 0 || k > list.len() {
+        return None;
+    }
+    let index = select(keyed(list, |item| Reverse(key(item))), k - 1);
+    Some(&list[index])
+}
+
+/// 中位数; 元素个数为偶数时返回中间两个中较小的那个
+pub fn median<T: Ord>(list: &[T]) -> Option<&T> {
+    median_by_key(list, |item| item)
+}
+
+pub fn median_by_key<'a, T, K, F>(list: &'a [T], key: F) -> Option<&'a T>
+where
+    K: Ord,
+    F: FnMut(&'a T) -> K,
+{
+    if list.is_empty() {
+        return None;
+    }
+    let index = select(keyed(list, key), (list.len() - 1) / 2);
+    Some(&list[index])
+}
+
+/// 每个元素的 (键, 下标), 下标让所有元素互不相等, 相同的键按出现顺序排列
+fn keyed<'a, T, K>(list: &'a [T], mut key: impl FnMut(&'a T) -> K) -> Vec<(K, usize)> {
+    list.iter()
+        .enumerate()
+        .map(|(index, item)| (key(item), index))
+        .collect()
+}
+
+/// 快速选择: 返回升序排列后位于第 `n` 位(从 0 开始)的元素的原始下标
+///
+/// 元素互不相等, 所以结果与稳定排序一致, 重复的键也不会让划分退化.
+fn select<K: Ord>(mut items: Vec<(K, usize)>, n: usize) -> usize {
+    let (mut lo, mut hi) = (0, items.len());
+
+    loop {
+        if hi - lo == 1 {
+            return items[lo].1;
+        }
+
+        // 三数取中, 把中间的那个放到末尾作为基准
+        let last = hi - 1;
+        let mid = lo + (hi - lo) / 2;
+        if items[mid] < items[lo] {
+            items.swap(mid, lo);
+        }
+        if items[last] < items[lo] {
+            items.swap(last, lo);
+        }
+        if items[mid] < items[last] {
+            items.swap(mid, last);
+        }
+
+        let mut store = lo;
+        for i in lo..last {
+            if items[i] < items[last] {
+                items.swap(i, store);
+                store += 1;
+            }
+        }
+        items.swap(store, last);
+
+        match n.cmp(&store) {
+            Ordering::Equal => return items[store].1,
+            Ordering::Less => hi = store,
+            Ordering::Greater => lo = store + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定种子的 xorshift 随机数, 用来生成测试数据
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        /// 长度 0..50, 取值范围较小以便出现重复
+        fn list(&mut self) -> Vec<i32> {
+            let len = self.below(50) as usize;
+            (0..len).map(|_| self.below(20) as i32 - 10).collect()
+        }
+    }
+
+    fn cases() -> impl Iterator<Item = Vec<i32>> {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        (0..500).map(move |_| rng.list())
+    }
+
+    fn sorted(list: &[i32]) -> Vec<i32> {
+        let mut sorted = list.to_vec();
+        sorted.sort();
+        sorted
+    }
+
+    /// `item` 在 `list` 中的下标(按地址比较)
+    fn index_of<T>(list: &[T], item: &T) -> usize {
+        list.iter().position(|x| std::ptr::eq(x, item)).unwrap()
+    }
+
+    #[test]
+    fn empty_lists() {
+        let empty: [i32; 0] = [];
+
+        assert_eq!(None, largest(&empty));
+        assert_eq!(None, smallest(&empty));
+        assert_eq!(None, min_max(&empty));
+        assert!(top_k(&empty, 3).is_empty());
+        assert_eq!(None, kth_largest(&empty, 1));
+        assert_eq!(None, median(&empty));
+    }
+
+    #[test]
+    fn works_with_partial_ord() {
+        let floats = [1.5, -0.5, 4.0, 2.25];
+
+        assert_eq!(Some(&4.0), largest(&floats));
+        assert_eq!(Some((&-0.5, &4.0)), min_max(&floats));
+        assert_eq!(Some(&'y'), largest(&['y', 'm', 'a', 'q']));
+    }
+
+    #[test]
+    fn largest_and_smallest_match_sorting() {
+        for list in cases() {
+            let sorted = sorted(&list);
+
+            assert_eq!(sorted.last(), largest(&list));
+            assert_eq!(sorted.first(), smallest(&list));
+            assert_eq!(
+                sorted.first().zip(sorted.last()),
+                min_max(&list),
+                "{:?}",
+                list
+            );
+        }
+    }
+
+    #[test]
+    fn ties_return_first_occurrence() {
+        for list in cases().filter(|list| !list.is_empty()) {
+            let max = largest(&list).unwrap();
+            let (min, max2) = min_max(&list).unwrap();
+
+            assert_eq!(
+                list.iter().position(|x| x == max),
+                Some(index_of(&list, max))
+            );
+            assert_eq!(index_of(&list, max), index_of(&list, max2));
+            assert_eq!(
+                list.iter().position(|x| x == min),
+                Some(index_of(&list, min))
+            );
+        }
+    }
+
+    #[test]
+    fn top_k_matches_sorting() {
+        let mut rng = Rng(7);
+        for list in cases() {
+            let k = rng.below(list.len() as u64 + 3) as usize;
+            let mut expected = sorted(&list);
+            expected.reverse();
+            expected.truncate(k);
+
+            let top: Vec<i32> = top_k(&list, k).into_iter().copied().collect();
+            assert_eq!(expected, top, "k = {} in {:?}", k, list);
+        }
+    }
+
+    #[test]
+    fn kth_largest_and_median_match_sorting() {
+        for list in cases() {
+            let sorted = sorted(&list);
+
+            for k in 1..=list.len() {
+                assert_eq!(Some(&sorted[list.len() - k]), kth_largest(&list, k));
+            }
+            assert_eq!(None, kth_largest(&list, 0));
+            assert_eq!(None, kth_largest(&list, list.len() + 1));
+            assert_eq!(sorted.get(list.len().wrapping_sub(1) / 2), median(&list));
+        }
+    }
+
+    #[test]
+    fn key_functions() {
+        let words = ["apple", "fig", "banana", "kiwi", "cherry", "plum"];
+
+        assert_eq!(Some(&"banana"), largest_by_key(&words, |w| w.len()));
+        assert_eq!(Some(&"fig"), smallest_by_key(&words, |w| w.len()));
+        assert_eq!(
+            Some((&"fig", &"banana")),
+            min_max_by_key(&words, |w| w.len())
+        );
+        assert_eq!(
+            vec![&"banana", &"cherry", &"apple"],
+            top_k_by_key(&words, 3, |w| w.len())
+        );
+        assert_eq!(Some(&"cherry"), kth_largest_by_key(&words, 2, |w| w.len()));
+        // 长度排序后为 fig kiwi plum apple banana cherry
+        assert_eq!(Some(&"plum"), median_by_key(&words, |w| w.len()));
+    }
+
+    #[test]
+    fn key_variants_are_stable() {
+        let mut rng = Rng(99);
+        for _ in 0..200 {
+            let pairs: Vec<(i32, usize)> = (0..rng.below(30) as usize)
+                .map(|i| (rng.below(5) as i32, i))
+                .collect();
+            // 稳定排序: 键从大到小, 键相同的保持原有顺序
+            let mut descending = pairs.clone();
+            descending.sort_by_key(|&(key, _)| Reverse(key));
+            let mut ascending = pairs.clone();
+            ascending.sort_by_key(|&(key, _)| key);
+
+            for k in 1..=pairs.len() {
+                let kth = kth_largest_by_key(&pairs, k, |&(key, _)| key);
+                assert_eq!(Some(&descending[k - 1]), kth);
+                assert_eq!(kth, top_k_by_key(&pairs, k, |&(key, _)| key).pop());
+            }
+            assert_eq!(
+                ascending.get(pairs.len().wrapping_sub(1) / 2),
+                median_by_key(&pairs, |&(key, _)| key)
+            );
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! 找出内容几乎相同的推文和文章
+//!
+//! 正文先切分为词, 再把相邻的几个词组成 shingle, 两段文本的相似度就是 shingle 集合的
+//! Jaccard 系数. 逐对比较集合太慢, 所以每段文本先压缩成固定长度的 MinHash 签名,
+//! 两个签名中相等位置所占的比例就是 Jaccard 系数的估计值.
+
+use std::collections::HashSet;
+
+use crate::aggregator::Summary;
+use crate::text::is_wide;
+
+/// 切分为小写的词; 中日韩文字之间没有空格, 每个字单独算一个词
+pub fn tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if is_wide(c) && c.is_alphanumeric() {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            tokens.push(c.to_string());
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            tokens.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+/// 每 `size` 个相邻的词组成一个 shingle, 返回它们的哈希值
+///
+/// 不足 `size` 个词时整段文本算一个 shingle; 没有词时返回空集合.
+pub fn shingles(text: &str, size: usize) -> HashSet<u64> {
+    let tokens = tokens(text);
+    if tokens.is_empty() {
+        return HashSet::new();
+    }
+
+    let size = size.clamp(1, tokens.len());
+    tokens.windows(size).map(fnv1a).collect()
+}
+
+/// 两个集合的 Jaccard 系数: 交集大小除以并集大小, 两个空集合的系数为 0
+pub fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// shingle 集合的 MinHash 签名: 第 i 个值是集合在第 i 个哈希函数下的最小哈希值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    mins: Vec<u64>,
+}
+
+impl Signature {
+    /// 空集合的签名也是空的, 它与任何签名的相似度都是 0
+    pub fn new(shingles: &HashSet<u64>, num_hashes: usize) -> Signature {
+        if shingles.is_empty() {
+            return Signature { mins: Vec::new() };
+        }
+
+        let mins = (0..num_hashes as u64)
+            .map(|i| {
+                let seed = mix(i.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+                shingles
+                    .iter()
+                    .map(|&shingle| mix(shingle ^ seed))
+                    .min()
+                    .unwrap()
+            })
+            .collect();
+        Signature { mins }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mins.is_empty()
+    }
+
+    /// Jaccard 系数的估计值
+    ///
+    /// 两个签名使用的哈希函数数量不同时无法比较, 返回 `None`; 空签名与任何签名的相似度都是 0.
+    pub fn similarity(&self, other: &Signature) -> Option<f64> {
+        if self.is_empty() || other.is_empty() {
+            return Some(0.0);
+        }
+        if self.mins.len() != other.mins.len() {
+            return None;
+        }
+
+        let equal = self
+            .mins
+            .iter()
+            .zip(&other.mins)
+            .filter(|(a, b)| a == b)
+            .count();
+        Some(equal as f64 / self.mins.len() as f64)
+    }
+}
+
+/// 把相似度不低于 `threshold` 的内容归为一组
+///
+/// 相似关系是传递的: a 与 b 相似、b 与 c 相似时, a、b、c 属于同一组.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduplicator {
+    /// 每个 shingle 包含的词数; 推文很短, 太大会让改了一个词的转推也不再相似
+    pub shingle_size: usize,
+    /// MinHash 签名的长度, 越长估计越准
+    pub num_hashes: usize,
+    pub threshold: f64,
+}
+
+impl Default for Deduplicator {
+    fn default() -> Deduplicator {
+        Deduplicator {
+            shingle_size: 2,
+            num_hashes: 128,
+            threshold: 0.6,
+        }
+    }
+}
+
+impl Deduplicator {
+    pub fn signature(&self, item: &dyn Summary) -> Signature {
+        Signature::new(
+            &shingles(&item.content(), self.shingle_size),
+            self.num_hashes,
+        )
+    }
+
+    /// 分组结果是 `items` 中的下标; 每组按下标升序, 各组按第一个下标升序
+    ///
+    /// 正文为空的内容不与任何内容相似, 各自单独成组.
+    ///
+    /// 两两比较所有签名, 耗时为 O(n² · `num_hashes`), 适合一页或一期摘要这样最多几千条的内容;
+    /// 更大的集合需要先按签名分段(LSH banding)筛选候选对.
+    pub fn clusters(&self, items: &[&dyn Summary]) -> Vec<Vec<usize>> {
+        let signatures: Vec<Signature> = items.iter().map(|&item| self.signature(item)).collect();
+        let mut sets = DisjointSet::new(items.len());
+
+        for (i, a) in signatures.iter().enumerate() {
+            for (j, b) in signatures.iter().enumerate().skip(i + 1) {
+                if a.similarity(b).is_some_and(|s| s >= self.threshold) {
+                    sets.union(i, j);
+                }
+            }
+        }
+
+        let mut clusters: Vec<Vec<usize>> = Vec::new();
+        let mut cluster_of_root: Vec<Option<usize>> = vec![None; items.len()];
+        for i in 0..items.len() {
+            let root = sets.find(i);
+            match cluster_of_root[root] {
+                Some(cluster) => clusters[cluster].push(i),
+                None => {
+                    cluster_of_root[root] = Some(clusters.len());
+                    clusters.push(vec![i]);
+                }
+            }
+        }
+        clusters
+    }
+
+    /// 每组只保留最先出现的那一条, 保持原有顺序
+    pub fn representatives<'a>(&self, items: &[&'a dyn Summary]) -> Vec<&'a dyn Summary> {
+        self.clusters(items)
+            .into_iter()
+            .map(|cluster| items[cluster[0]])
+            .collect()
+    }
+}
+
+/// 并查集, 用来合并相似的内容
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            // 路径减半
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    /// 较小的下标作为根
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a.max(b)] = a.min(b);
+    }
+}
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// splitmix64 的混合函数, 把相近的输入打散
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a 哈希, 词与词之间用 0xff 分隔; 与 `DefaultHasher` 不同, 结果不随 Rust 版本变化
+fn fnv1a(words: &[String]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for (i, word) in words.iter().enumerate() {
+        let separator: &[u8] = if i == 0 { &[] } else { &[0xff] };
+        for &byte in separator.iter().chain(word.as_bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::{NewsArticle, Tweet};
+
+    fn tweet(username: &str, content: &str) -> Tweet {
+        Tweet {
+            username: String::from(username),
+            content: String::from(content),
+            reply: false,
+            retweet: false,
+        }
+    }
+
+    #[test]
+    fn tokens_are_lowercase_words() {
+        assert_eq!(
+            vec!["rt", "rustlang", "rust", "1", "0", "is", "here"],
+            tokens("RT @rustlang: [Rust 1.0] is_here!")
+        );
+        assert_eq!(vec!["rust", "很", "快"], tokens("Rust很快！"));
+        assert!(tokens(" ... ").is_empty());
+    }
+
+    #[test]
+    fn shingles_of_short_texts() {
+        assert_eq!(3, shingles("a b c d", 2).len());
+        assert_eq!(shingles("hello world", 5), shingles("Hello, World!", 2));
+        assert_eq!(1, shingles("hello world", 5).len());
+        assert!(shingles("", 2).is_empty());
+    }
+
+    #[test]
+    fn exact_jaccard() {
+        let a: HashSet<u64> = [1, 2, 3, 4].into_iter().collect();
+        let b: HashSet<u64> = [3, 4, 5, 6].into_iter().collect();
+
+        assert_eq!(2.0 / 6.0, jaccard(&a, &b));
+        assert_eq!(1.0, jaccard(&a, &a));
+        assert_eq!(0.0, jaccard(&HashSet::new(), &HashSet::new()));
+    }
+
+    #[test]
+    fn signatures_estimate_jaccard() {
+        for overlap in [0u64, 25, 50, 75, 100] {
+            let a: HashSet<u64> = (0..100).collect();
+            let b: HashSet<u64> = (100 - overlap..200 - overlap).collect();
+            let exact = jaccard(&a, &b);
+            let estimate = Signature::new(&a, 256)
+                .similarity(&Signature::new(&b, 256))
+                .unwrap();
+
+            assert!(
+                (exact - estimate).abs() < 0.1,
+                "overlap {}: exact {} estimate {}",
+                overlap,
+                exact,
+                estimate
+            );
+        }
+    }
+
+    #[test]
+    fn empty_signatures_are_never_similar() {
+        let empty = Signature::new(&HashSet::new(), 16);
+
+        assert!(empty.is_empty());
+        assert_eq!(Some(0.0), empty.similarity(&empty));
+    }
+
+    #[test]
+    fn signatures_of_different_lengths_are_not_comparable() {
+        let shingles: HashSet<u64> = (0..10).collect();
+        let short = Signature::new(&shingles, 16);
+        let long = Signature::new(&shingles, 32);
+
+        assert_eq!(None, short.similarity(&long));
+        assert_eq!(Some(1.0), long.similarity(&long));
+        assert_eq!(
+            Some(0.0),
+            Signature::new(&HashSet::new(), 16).similarity(&long)
+        );
+    }
+
+    #[test]
+    fn clusters_near_duplicates() {
+        let original = tweet(
+            "rustlang",
+            "Rust 1.0 is here! Read the announcement on the blog.",
+        );
+        let retweet = tweet(
+            "horse_ebooks",
+            "RT @rustlang: Rust 1.0 is here! Read the announcement on the blog.",
+        );
+        let shouting = tweet(
+            "fan",
+            "RUST 1.0 IS HERE!!! read the announcement on the blog",
+        );
+        let unrelated = tweet(
+            "horse_ebooks",
+            "of course, as you probably already know, people",
+        );
+        let article = NewsArticle {
+            headline: String::from("Rust 1.0 released"),
+            location: String::from("Internet"),
+            author: String::from("ferris"),
+            content: String::from("Rust 1.0 is here! Read the announcement on the blog."),
+        };
+        let items: [&dyn Summary; 5] = [&original, &unrelated, &retweet, &article, &shouting];
+
+        let dedup = Deduplicator::default();
+        assert_eq!(vec![vec![0, 2, 3, 4], vec![1]], dedup.clusters(&items));
+
+        let kept: Vec<String> = dedup
+            .representatives(&items)
+            .iter()
+            .map(|item| item.summarize_author())
+            .collect();
+        assert_eq!(vec!["@rustlang", "@horse_ebooks"], kept);
+    }
+
+    #[test]
+    fn threshold_controls_grouping() {
+        let a = tweet("a", "the quick brown fox jumps over the lazy dog");
+        let b = tweet("b", "the quick brown fox jumps over the sleepy cat");
+        let items: [&dyn Summary; 2] = [&a, &b];
+
+        let strict = Deduplicator {
+            threshold: 0.9,
+            ..Deduplicator::default()
+        };
+        let loose = Deduplicator {
+            threshold: 0.3,
+            ..Deduplicator::default()
+        };
+        assert_eq!(2, strict.clusters(&items).len());
+        assert_eq!(1, loose.clusters(&items).len());
+    }
+
+    #[test]
+    fn empty_content_is_not_a_duplicate() {
+        let a = tweet("a", "");
+        let b = tweet("b", "!!!");
+        let items: [&dyn Summary; 2] = [&a, &b];
+
+        assert_eq!(
+            vec![vec![0], vec![1]],
+            Deduplicator::default().clusters(&items)
+        );
+    }
+
+    #[test]
+    fn clustering_is_transitive() {
+        let a = tweet("a", "one two three four five six");
+        let b = tweet("b", "one two three four seven eight");
+        let c = tweet("c", "nine ten three four seven eight");
+        let items: [&dyn Summary; 3] = [&a, &c, &b];
+        let dedup = Deduplicator {
+            threshold: 0.3,
+            ..Deduplicator::default()
+        };
+
+        assert!(
+            dedup
+                .signature(&a)
+                .similarity(&dedup.signature(&c))
+                .unwrap()
+                < 0.3
+        );
+        assert_eq!(vec![vec![0, 1, 2]], dedup.clusters(&items));
+    }
+}
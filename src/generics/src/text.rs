@@ -0,0 +1,238 @@
+use std::borrow::Cow;
+
+const ELLIPSIS: char = '…';
+
+/// 按用户看到的字符(近似的字素簇)切分文本
+///
+/// 不依赖 Unicode 数据表, 只处理常见情况: 组合附加符号、变体选择符、肤色修饰符、
+/// 零宽连接符组成的 emoji 序列、国旗(成对的区域指示符)、韩文字母组合以及 `\r\n`.
+/// 中日韩文字每个字符就是一个字素.
+pub fn graphemes(text: &str) -> Graphemes<'_> {
+    Graphemes { text }
+}
+
+pub struct Graphemes<'a> {
+    text: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.text.char_indices();
+        let (_, first) = chars.next()?;
+
+        let mut prev = first;
+        let mut regional = usize::from(is_regional_indicator(first));
+        let mut end = self.text.len();
+
+        for (i, c) in chars {
+            let joins = (prev == '\r' && c == '\n')
+                || is_extending(c)
+                || (prev == ZWJ && !c.is_whitespace())
+                || (regional == 1 && is_regional_indicator(c))
+                || (is_hangul_lead(prev) && is_hangul_vowel(c))
+                || (is_hangul_vowel(prev) && is_hangul_trail(c));
+
+            if !joins {
+                end = i;
+                break;
+            }
+            if is_regional_indicator(c) {
+                regional += 1;
+            }
+            prev = c;
+        }
+
+        let (cluster, rest) = self.text.split_at(end);
+        self.text = rest;
+        Some(cluster)
+    }
+}
+
+const ZWJ: char = '\u{200D}';
+
+fn is_extending(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{3099}'..='\u{309A}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{E0100}'..='\u{E01EF}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+        | ZWJ)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn is_hangul_lead(c: char) -> bool {
+    ('\u{1100}'..='\u{115F}').contains(&c)
+}
+
+fn is_hangul_vowel(c: char) -> bool {
+    ('\u{1160}'..='\u{11A7}').contains(&c)
+}
+
+fn is_hangul_trail(c: char) -> bool {
+    ('\u{11A8}'..='\u{11FF}').contains(&c)
+}
+
+/// 在等宽终端或纯文本邮件中占的列数: 中日韩文字、全角符号和 emoji 占两列, 其余字素占一列
+pub fn display_width(text: &str) -> usize {
+    graphemes(text)
+        .map(|cluster| match cluster.chars().next() {
+            Some(c) if is_wide(c) => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+pub(crate) fn is_wide(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{33FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F1E6}'..='\u{1F1FF}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}')
+}
+
+/// 截断到最多 `max` 个字素, 被截断时以 `…` 结尾(省略号也计入 `max`)
+pub fn truncate(text: &str, max: usize) -> Cow<'_, str> {
+    let mut clusters = graphemes(text);
+    let mut end = 0;
+
+    for _ in 0..max {
+        match clusters.next() {
+            Some(cluster) => end += cluster.len(),
+            None => return Cow::Borrowed(text),
+        }
+    }
+    if clusters.next().is_none() {
+        return Cow::Borrowed(text);
+    }
+    if max == 0 {
+        return Cow::Borrowed("");
+    }
+
+    // 去掉最后一个字素给省略号腾出位置
+    let kept = &text[..end];
+    let last = graphemes(kept).last().map_or(0, str::len);
+    let kept = kept[..kept.len() - last].trim_end();
+    Cow::Owned(format!("{}{}", kept, ELLIPSIS))
+}
+
+/// 第一句话: 到第一个后面跟着空白或文本结尾的 `.`、`!`、`?` 为止, 或到第一个 `。`、`！`、`？` 为止
+///
+/// 没有句末标点时返回整段文本(去掉首尾空白).
+pub fn first_sentence(text: &str) -> &str {
+    let text = text.trim();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let end = i + c.len_utf8();
+        match c {
+            '。' | '！' | '？' => return &text[..end],
+            '.' | '!' | '?' => match chars.peek() {
+                None => return text,
+                Some(&(_, next)) if next.is_whitespace() => return &text[..end],
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clusters(text: &str) -> Vec<&str> {
+        graphemes(text).collect()
+    }
+
+    #[test]
+    fn graphemes_keep_combined_characters() {
+        assert_eq!(vec!["e\u{301}", "a"], clusters("e\u{301}a"));
+        assert_eq!(vec!["中", "文"], clusters("中文"));
+        assert_eq!(vec!["\r\n", "x"], clusters("\r\nx"));
+        // 国旗由两个区域指示符组成
+        assert_eq!(vec!["🇨🇳", "🇯🇵"], clusters("🇨🇳🇯🇵"));
+        // 👩‍💻 与肤色修饰
+        assert_eq!(vec!["👩\u{200D}💻", "👍🏽", "!"], clusters("👩\u{200D}💻👍🏽!"));
+        assert_eq!(
+            vec!["\u{1100}\u{1161}\u{11A8}", "a"],
+            clusters("\u{1100}\u{1161}\u{11A8}a")
+        );
+        assert!(clusters("").is_empty());
+    }
+
+    #[test]
+    fn truncate_short_text_is_borrowed() {
+        assert!(matches!(truncate("hello", 5), Cow::Borrowed("hello")));
+        assert_eq!("", truncate("hello", 0));
+    }
+
+    #[test]
+    fn truncate_adds_ellipsis() {
+        assert_eq!("hell…", truncate("hello world", 5));
+        assert_eq!("hello…", truncate("hello world", 7));
+        assert_eq!("…", truncate("hello", 1));
+    }
+
+    #[test]
+    fn truncate_cjk() {
+        let text = "企鹅队再次夺得斯坦利杯冠军";
+
+        assert_eq!("企鹅队再…", truncate(text, 5));
+        assert_eq!(5, truncate(text, 5).chars().count());
+        assert_eq!(text, truncate(text, 13));
+    }
+
+    #[test]
+    fn truncate_never_splits_a_cluster() {
+        assert_eq!(
+            "e\u{301}e\u{301}…",
+            truncate("e\u{301}e\u{301}e\u{301}e\u{301}", 3)
+        );
+        assert_eq!("🇨🇳…", truncate("🇨🇳🇯🇵🇰🇷", 2));
+    }
+
+    #[test]
+    fn width_of_mixed_text() {
+        assert_eq!(5, display_width("hello"));
+        assert_eq!(4, display_width("中文"));
+        assert_eq!(9, display_width("Rust 博客"));
+        assert_eq!(4, display_width("e\u{301}🇨🇳!"));
+        assert_eq!(4, display_width("ＡＢ"));
+    }
+
+    #[test]
+    fn first_sentence_rules() {
+        assert_eq!("One.", first_sentence("One. Two."));
+        assert_eq!(
+            "Version 1.2 is out!",
+            first_sentence("Version 1.2 is out! Update now.")
+        );
+        assert_eq!("Really?", first_sentence("  Really?"));
+        assert_eq!("第一句。", first_sentence("第一句。第二句。"));
+        assert_eq!("no terminator", first_sentence("no terminator "));
+        assert_eq!("", first_sentence(""));
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::Path;
+
+use generics::aggregator::{NewsArticle, Summary, Tweet};
+use generics::render::{HtmlRenderer, MarkdownRenderer, Renderer, TextRenderer};
+
+fn golden(name: &str) -> String {
+    fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn items() -> (NewsArticle, Tweet, Tweet) {
+    (
+        NewsArticle {
+            headline: String::from("Penguins win the Stanley Cup Championship!"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from("Iceburgh"),
+            content: String::from(
+                "The Penguins beat the <Red Wings> & the *Sharks* on the way to the Cup. \
+                 More at 11.",
+            ),
+        },
+        Tweet {
+            username: String::from("rust_zh"),
+            content: String::from(
+                "@ferris 是的！Rust 的所有权系统让内存安全与高性能兼得，无需垃圾回收器，这正是我们选择它的原因。",
+            ),
+            reply: true,
+            retweet: false,
+        },
+        Tweet {
+            username: String::from("horse_ebooks"),
+            content: String::from("RT @rustlang: [Rust 1.0] is_here `today`"),
+            reply: false,
+            retweet: true,
+        },
+    )
+}
+
+fn render(renderer: &dyn Renderer) -> String {
+    let (article, reply, retweet) = items();
+    let items: [&dyn Summary; 3] = [&article, &reply, &retweet];
+    renderer.render("Weekly digest", &items)
+}
+
+#[test]
+fn html_matches_golden_file() {
+    assert_eq!(golden("digest.html"), render(&HtmlRenderer));
+}
+
+#[test]
+fn markdown_matches_golden_file() {
+    assert_eq!(golden("digest.md"), render(&MarkdownRenderer));
+}
+
+#[test]
+fn text_matches_golden_file() {
+    assert_eq!(golden("digest.txt"), render(&TextRenderer::default()));
+}
+
+#[test]
+fn text_lines_fit_width() {
+    let text = render(&TextRenderer { width: 40 });
+
+    assert!(text
+        .lines()
+        .all(|line| generics::text::display_width(line) <= 40));
+}
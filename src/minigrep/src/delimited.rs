@@ -0,0 +1,266 @@
+use std::error::Error;
+use std::fmt;
+
+/// 分隔符格式的一条记录
+///
+/// `raw` 为记录在原文中的文本(不含行尾换行符), `fields` 为解析后的各列内容.
+#[derive(Debug, PartialEq)]
+pub struct Record<'a> {
+    pub raw: &'a str,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "第 {} 行的引号未闭合", self.line)
+    }
+}
+
+impl Error for ParseError {}
+
+/// 按 CSV 规则解析: 逗号分隔, 双引号包裹的字段中可以包含逗号和换行, `""` 表示一个双引号
+pub fn parse_csv(contents: &str) -> Result<Vec<Record<'_>>, ParseError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let mut chars = contents.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek().map(|&(_, next)| next) == Some('"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => fields.push(std::mem::take(&mut field)),
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                let raw = contents[start..i]
+                    .strip_suffix('\r')
+                    .unwrap_or(&contents[start..i]);
+                records.push(Record {
+                    raw,
+                    fields: std::mem::take(&mut fields),
+                });
+                start = i + 1;
+                line += 1;
+                record_line = line;
+            }
+            '\r' if chars.peek().map(|&(_, next)| next) == Some('\n') => {}
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ParseError { line: record_line });
+    }
+
+    // 最后一条记录没有以换行结尾
+    if start < contents.len() {
+        fields.push(field);
+        records.push(Record {
+            raw: &contents[start..],
+            fields,
+        });
+    }
+
+    Ok(records)
+}
+
+/// 按 TSV 规则解析: 每行一条记录, 以制表符分隔, 不支持引号
+pub fn parse_tsv(contents: &str) -> Vec<Record<'_>> {
+    contents
+        .lines()
+        .map(|line| Record {
+            raw: line,
+            fields: line.split('\t').map(String::from).collect(),
+        })
+        .collect()
+}
+
+/// 根据列名或列号(从 1 开始)找到列的下标
+///
+/// 使用列名时, 第一条记录被视为表头.
+pub fn column_index(selector: &str, header: Option<&Record>) -> Result<usize, String> {
+    if let Ok(number) = selector.parse::<usize>() {
+        return match number {
+            0 => Err(String::from("列号从 1 开始")),
+            n => Ok(n - 1),
+        };
+    }
+
+    header
+        .and_then(|header| header.fields.iter().position(|name| name == selector))
+        .ok_or_else(|| format!("未找到列: {}", selector))
+}
+
+/// 在记录中搜索
+///
+/// 指定了 `column` 时, 该列的内容必须与查询字符串完全相同(`status=500` 不会匹配 `1500`);
+/// `column` 为 `None` 时, 任意一列包含查询字符串即匹配.
+pub fn search_records<'a>(
+    query: &str,
+    records: &[Record<'a>],
+    column: Option<usize>,
+    case_sensitive: bool,
+) -> Vec<&'a str> {
+    records
+        .iter()
+        .filter(|record| {
+            selected_fields(record, column)
+                .any(|field| field_matches(query, field, column.is_some(), case_sensitive))
+        })
+        .map(|record| record.raw)
+        .collect()
+}
+
+/// 单个字段是否匹配, `exact` 为真时比较是否相等, 否则比较是否包含
+pub fn field_matches(query: &str, field: &str, exact: bool, case_sensitive: bool) -> bool {
+    match (exact, case_sensitive) {
+        (true, true) => field == query,
+        (true, false) => field.to_lowercase() == query.to_lowercase(),
+        (false, true) => field.contains(query),
+        (false, false) => field.to_lowercase().contains(&query.to_lowercase()),
+    }
+}
+
+/// 参与匹配的列, `column` 为 `None` 时为全部列
+pub fn selected_fields<'r>(
+    record: &'r Record,
+    column: Option<usize>,
+) -> impl Iterator<Item = &'r String> {
+    let fields = match column {
+        Some(index) => record.fields.get(index..=index).unwrap_or_default(),
+        None => &record.fields[..],
+    };
+
+    fields.iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quoted_fields() {
+        let contents = "\
+id,name,status
+1,\"Smith, John\",200
+2,\"say \"\"hi\"\"\",500";
+
+        let records = parse_csv(contents).unwrap();
+
+        assert_eq!(3, records.len());
+        assert_eq!(vec!["1", "Smith, John", "200"], records[1].fields);
+        assert_eq!(vec!["2", "say \"hi\"", "500"], records[2].fields);
+        assert_eq!("2,\"say \"\"hi\"\"\",500", records[2].raw);
+    }
+
+    #[test]
+    fn csv_newline_in_quotes() {
+        let contents = "1,\"two\nlines\",x\r\n2,one,y\r\n";
+
+        let records = parse_csv(contents).unwrap();
+
+        assert_eq!(2, records.len());
+        assert_eq!("1,\"two\nlines\",x", records[0].raw);
+        assert_eq!(vec!["1", "two\nlines", "x"], records[0].fields);
+        assert_eq!("2,one,y", records[1].raw);
+        assert_eq!(vec!["2", "one", "y"], records[1].fields);
+    }
+
+    #[test]
+    fn csv_unterminated_quote() {
+        let contents = "a,b\nc,\"d\ne,f";
+
+        assert_eq!(Err(ParseError { line: 2 }), parse_csv(contents));
+    }
+
+    #[test]
+    fn tsv_fields() {
+        let records = parse_tsv("a\tb,c\t\"d\"\n");
+
+        assert_eq!(1, records.len());
+        assert_eq!(vec!["a", "b,c", "\"d\""], records[0].fields);
+    }
+
+    #[test]
+    fn column_by_name_or_number() {
+        let records = parse_csv("id,status\n1,500").unwrap();
+
+        assert_eq!(Ok(1), column_index("status", records.first()));
+        assert_eq!(Ok(0), column_index("1", records.first()));
+        assert!(column_index("0", records.first()).is_err());
+        assert!(column_index("missing", records.first()).is_err());
+    }
+
+    #[test]
+    fn search_in_column() {
+        let contents = "\
+id,path,status
+1,/500,200
+2,/index,500
+3,/Status,404";
+        let records = parse_csv(contents).unwrap();
+
+        assert_eq!(
+            vec!["2,/index,500"],
+            search_records("500", &records[1..], Some(2), true)
+        );
+        assert_eq!(
+            vec!["1,/500,200", "2,/index,500"],
+            search_records("500", &records[1..], None, true)
+        );
+        assert_eq!(
+            vec!["id,path,status", "3,/Status,404"],
+            search_records("STATUS", &records, None, false)
+        );
+    }
+
+    #[test]
+    fn column_search_is_exact() {
+        let contents = "\
+id,status
+1,1500
+2,5000
+3,500
+4,Ok";
+        let records = parse_csv(contents).unwrap();
+
+        assert_eq!(
+            vec!["3,500"],
+            search_records("500", &records[1..], Some(1), true)
+        );
+        assert_eq!(
+            vec!["1,1500", "2,5000", "3,500"],
+            search_records("500", &records[1..], None, true)
+        );
+        assert_eq!(
+            vec!["4,Ok"],
+            search_records("OK", &records[1..], Some(1), false)
+        );
+        assert!(search_records("OK", &records[1..], Some(1), true).is_empty());
+    }
+}
@@ -1,54 +1,218 @@
 use std::env;
-use std::env::Args;
 use std::error::Error;
 use std::fs;
+use std::time::Instant;
+
+use delimited::Record;
+use stats::Stats;
+
+pub mod delimited;
+pub mod output;
+pub mod stats;
+
+/// 输入文件的格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// 按行搜索
+    Lines,
+    Csv,
+    Tsv,
+}
 
 pub struct Config {
     pub query: String,
-    pub filename: String,
+    /// 搜索的文件, 多于一个时输出的每行前会加上文件名
+    pub filenames: Vec<String>,
     pub case_sensitive: bool,
+    pub format: Format,
+    /// 只在该列中搜索, 可以是列名或从 1 开始的列号
+    pub field: Option<String>,
+    /// 输出结果后, 在标准错误中打印统计信息
+    pub stats: bool,
+    /// 只输出匹配到的文本, 而不是整行
+    pub only_matching: bool,
+    /// 排序并去重后输出
+    pub unique: bool,
+    /// 按出现次数输出最多的前 N 个结果
+    pub top: Option<usize>,
 }
 
 impl Config {
-    pub fn new(mut args: Args) -> Result<Config, &'static str> {
+    pub fn new(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
         args.next(); // 第一个迭代的为程序的名字
 
-        let query = match args.next() {
+        let mut format = Format::Lines;
+        let mut field = None;
+        let mut query = None;
+        let mut stats = false;
+        let mut only_matching = false;
+        let mut unique = false;
+        let mut top = None;
+        let mut positional = Vec::new();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--csv" => format = Format::Csv,
+                "--tsv" => format = Format::Tsv,
+                "--stats" => stats = true,
+                "--only-matching" => only_matching = true,
+                "--unique" => unique = true,
+                "--top" => {
+                    let n = args.next().and_then(|n| n.parse().ok());
+                    top = Some(n.ok_or("--top 需要一个数字参数")?);
+                }
+                "--field" => {
+                    // --field 列=查询字符串
+                    let value = args.next().ok_or("--field 缺少参数")?;
+                    let (name, value) = value.split_once('=').ok_or("--field 的格式应为 列=值")?;
+                    field = Some(name.to_string());
+                    query = Some(value.to_string());
+                }
+                _ if arg.starts_with("--") => return Err("未知的选项"),
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+
+        let query = match query.or_else(|| positional.next()) {
             Some(v) => v,
             None => return Err("未获取到需要搜索的字符串"),
         };
-        let filename = match args.next() {
-            Some(v) => v,
-            None => return Err("未获取到需要搜索的文件名"),
-        };
+        let filenames: Vec<String> = positional.collect();
+        if filenames.is_empty() {
+            return Err("未获取到需要搜索的文件名");
+        }
+
+        if field.is_some() && format == Format::Lines {
+            return Err("--field 需要配合 --csv 或 --tsv 使用");
+        }
 
         let case_sensitive = env::var("CASE_INSENSITIVE").is_err();
 
         Ok(Config {
             query,
-            filename,
+            filenames,
             case_sensitive,
+            format,
+            field,
+            stats,
+            only_matching,
+            unique,
+            top,
         })
     }
 }
 
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
+    let start = Instant::now();
+    let mut stats = Stats::default();
+    // --unique 和 --top 需要汇总所有文件的结果后再输出
+    let aggregate = config.unique || config.top.is_some();
+    let mut collected = Vec::new();
 
-    // println!("文件内容: \n{}", contents);
+    for filename in &config.filenames {
+        let contents = fs::read_to_string(filename)?;
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
-    } else {
+        // println!("文件内容: \n{}", contents);
+
+        let results = search_contents(&config, &contents)?;
+
+        let matches = if config.only_matching || config.stats {
+            only_matching(&config, &contents, &results)?
+        } else {
+            Vec::new()
+        };
+        let match_count = matches.len();
+        let lines = if config.only_matching {
+            matches
+        } else {
+            results.iter().map(|line| line.to_string()).collect()
+        };
+
+        if aggregate {
+            collected.extend(lines);
+        } else {
+            for line in lines {
+                if config.filenames.len() > 1 {
+                    println!("{}:{}", filename, line);
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+
+        stats.add_file(filename, contents.len(), results.len(), match_count);
+    }
+
+    if let Some(n) = config.top {
+        for (count, line) in output::top(&collected, n) {
+            println!("{:>7} {}", count, line);
+        }
+    } else if config.unique {
+        for line in output::unique(collected) {
+            println!("{}", line);
+        }
+    }
+
+    if config.stats {
+        stats.elapsed = start.elapsed();
+        eprint!("{}", stats);
+    }
+
+    Ok(())
+}
+
+/// 按配置的格式搜索一个文件的内容
+pub fn search_contents<'a>(
+    config: &Config,
+    contents: &'a str,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let results = match config.format {
+        Format::Lines if config.case_sensitive => search(&config.query, contents),
         // 忽略大小写搜索
-        search_case_insensitive(&config.query, &contents)
+        Format::Lines => search_case_insensitive(&config.query, contents),
+        Format::Csv | Format::Tsv => search_delimited(config, contents)?,
     };
 
-    for line in results {
-        println!("{}", line);
+    Ok(results)
+}
+
+/// 从搜索结果中取出匹配到的文本, 每处匹配一个
+///
+/// CSV/TSV 格式只在参与匹配的列中查找, 因此需要重新解析记录.
+fn only_matching(
+    config: &Config,
+    contents: &str,
+    results: &[&str],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let matched = |text: &str| -> Vec<String> {
+        find_matches(&config.query, text, config.case_sensitive)
+            .into_iter()
+            .map(String::from)
+            .collect()
+    };
+
+    if config.format == Format::Lines {
+        return Ok(results.iter().flat_map(|line| matched(line)).collect());
     }
 
-    Ok(())
+    let (column, records) = delimited_records(config, contents)?;
+
+    Ok(records
+        .iter()
+        .flat_map(|record| delimited::selected_fields(record, column))
+        .flat_map(|field| match column {
+            // 指定列时整列必须相同, 匹配到的就是整个字段
+            Some(_)
+                if delimited::field_matches(&config.query, field, true, config.case_sensitive) =>
+            {
+                vec![field.clone()]
+            }
+            Some(_) => vec![],
+            None => matched(field),
+        })
+        .collect())
 }
 
 pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
@@ -59,6 +223,44 @@ pub fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
         .collect()
 }
 
+/// 按 CSV/TSV 记录搜索, 返回匹配记录的原文
+///
+/// 第一条记录总是被视为表头, 不参与匹配. 指定列时该列必须与查询字符串完全相同.
+pub fn search_delimited<'a>(
+    config: &Config,
+    contents: &'a str,
+) -> Result<Vec<&'a str>, Box<dyn Error>> {
+    let (column, records) = delimited_records(config, contents)?;
+
+    Ok(delimited::search_records(
+        &config.query,
+        &records,
+        column,
+        config.case_sensitive,
+    ))
+}
+
+/// 解析 CSV/TSV 记录, 返回要搜索的列和去掉表头后的记录
+fn delimited_records<'a>(
+    config: &Config,
+    contents: &'a str,
+) -> Result<(Option<usize>, Vec<Record<'a>>), Box<dyn Error>> {
+    let mut records = match config.format {
+        Format::Tsv => delimited::parse_tsv(contents),
+        _ => delimited::parse_csv(contents)?,
+    };
+
+    let column = match &config.field {
+        Some(field) => Some(delimited::column_index(field, records.first())?),
+        None => None,
+    };
+    if !records.is_empty() {
+        records.remove(0);
+    }
+
+    Ok((column, records))
+}
+
 pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
     let query = query.to_lowercase();
     // 使用迭代器版本
@@ -69,6 +271,58 @@ pub fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a st
         .collect()
 }
 
+/// 找出 `text` 中所有不重叠的匹配, 返回原文中被匹配到的部分
+///
+/// 忽略大小写时返回的是原文的写法, 例如在 "Rust" 中搜索 "rust" 得到 "Rust".
+pub fn find_matches<'a>(query: &str, text: &'a str, case_sensitive: bool) -> Vec<&'a str> {
+    if query.is_empty() {
+        return vec![];
+    }
+
+    if case_sensitive {
+        return text.match_indices(query).map(|(_, m)| m).collect();
+    }
+
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let mut matches = Vec::new();
+    let mut start = 0;
+
+    while let Some(c) = text[start..].chars().next() {
+        match match_len(&query, &text[start..]) {
+            Some(len) => {
+                matches.push(&text[start..start + len]);
+                start += len;
+            }
+            None => start += c.len_utf8(),
+        }
+    }
+
+    matches
+}
+
+/// `text` 的开头忽略大小写后与 `query` 相同时, 返回匹配部分的字节长度
+fn match_len(query: &[char], text: &str) -> Option<usize> {
+    let mut remaining = query;
+
+    for (i, c) in text.char_indices() {
+        if remaining.is_empty() {
+            return Some(i);
+        }
+        for lower in c.to_lowercase() {
+            match remaining.split_first() {
+                Some((&q, rest)) if q == lower => remaining = rest,
+                _ => return None,
+            }
+        }
+    }
+
+    if remaining.is_empty() {
+        Some(text.len())
+    } else {
+        None
+    }
+}
+
 /// Adds one to the number given.
 ///
 /// # Examples
@@ -125,4 +379,91 @@ Trust me.";
             search_case_insensitive(query, contents)
         );
     }
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        let mut all = vec![String::from("minigrep")];
+        all.extend(args.iter().map(|arg| arg.to_string()));
+        all.into_iter()
+    }
+
+    #[test]
+    fn config_field() {
+        let config = Config::new(args(&["--csv", "--field", "status=500", "data.csv"])).unwrap();
+
+        assert_eq!("500", config.query);
+        assert_eq!(vec!["data.csv"], config.filenames);
+        assert_eq!(Format::Csv, config.format);
+        assert_eq!(Some(String::from("status")), config.field);
+    }
+
+    #[test]
+    fn config_field_requires_format() {
+        assert!(Config::new(args(&["--field", "status=500", "data.csv"])).is_err());
+        assert!(Config::new(args(&["--tsv", "--field", "status", "data.tsv"])).is_err());
+    }
+
+    #[test]
+    fn delimited_named_field() {
+        let config = Config::new(args(&["--csv", "--field", "status=500", "data.csv"])).unwrap();
+        let contents = "\
+id,status
+1,200
+2,500";
+
+        assert_eq!(vec!["2,500"], search_delimited(&config, contents).unwrap());
+    }
+
+    #[test]
+    fn delimited_skips_header() {
+        let contents = "\
+id,status
+1,status
+2,500";
+
+        let numbered = Config::new(args(&["--csv", "--field", "2=status", "data.csv"])).unwrap();
+        assert_eq!(
+            vec!["1,status"],
+            search_delimited(&numbered, contents).unwrap()
+        );
+
+        let any = Config::new(args(&["--csv", "id", "data.csv"])).unwrap();
+        assert!(search_delimited(&any, contents).unwrap().is_empty());
+    }
+
+    #[test]
+    fn config_multiple_files() {
+        let config = Config::new(args(&["--stats", "to", "a.txt", "b.txt"])).unwrap();
+
+        assert_eq!("to", config.query);
+        assert_eq!(vec!["a.txt", "b.txt"], config.filenames);
+        assert!(config.stats);
+    }
+
+    #[test]
+    fn config_output_modes() {
+        let config = Config::new(args(&["--only-matching", "--top", "3", "to", "a.txt"])).unwrap();
+
+        assert!(config.only_matching);
+        assert!(!config.unique);
+        assert_eq!(Some(3), config.top);
+        assert!(Config::new(args(&["--top", "many", "to", "a.txt"])).is_err());
+    }
+
+    #[test]
+    fn matches_case_sensitive() {
+        assert_eq!(
+            vec!["to", "to"],
+            find_matches("to", "today, tomorrow, Tom", true)
+        );
+        assert!(find_matches("", "anything", true).is_empty());
+    }
+
+    #[test]
+    fn matches_case_insensitive() {
+        assert_eq!(
+            vec!["Rust", "rust", "RUST"],
+            find_matches("rUsT", "Rust: trust. RUST", false)
+        );
+        assert_eq!(vec!["äb", "Äb"], find_matches("ÄB", "xäby Äb", false));
+    }
 }
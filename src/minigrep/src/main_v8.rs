@@ -1,8 +1,11 @@
 use std::env;
 use std::process;
 
-use minigrep::run;
-use minigrep::Config;
+use lib_v8::run;
+use lib_v8::Config;
+
+// 第 8 版的库只属于这个教程程序, 不作为 minigrep 库的一部分
+mod lib_v8;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+/// 排序并去重, 相当于 `sort -u`
+pub fn unique(mut lines: Vec<String>) -> Vec<String> {
+    lines.sort();
+    lines.dedup();
+    lines
+}
+
+/// 统计每个结果出现的次数, 按次数从多到少返回前 `n` 个
+///
+/// 次数相同时按字典序排列, 相当于 `sort | uniq -c | sort -rn | head -n`.
+pub fn top(lines: &[String], n: usize) -> Vec<(usize, &str)> {
+    let mut counts = HashMap::new();
+
+    for line in lines {
+        *counts.entry(line.as_str()).or_insert(0) += 1;
+    }
+
+    let mut ranked: Vec<(usize, &str)> = counts
+        .into_iter()
+        .map(|(line, count)| (count, line))
+        .collect();
+
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+    ranked.truncate(n);
+
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn unique_sorts_and_dedups() {
+        assert_eq!(
+            lines(&["a", "b", "c"]),
+            unique(lines(&["c", "a", "b", "a", "c"]))
+        );
+    }
+
+    #[test]
+    fn top_ranks_by_count() {
+        let input = lines(&["b", "a", "c", "a", "b", "a", "d"]);
+
+        assert_eq!(vec![(3, "a"), (2, "b")], top(&input, 2));
+        assert_eq!(
+            vec![(3, "a"), (2, "b"), (1, "c"), (1, "d")],
+            top(&input, 10)
+        );
+        assert!(top(&input, 0).is_empty());
+    }
+}
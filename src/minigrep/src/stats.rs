@@ -0,0 +1,95 @@
+use std::fmt;
+use std::time::Duration;
+
+/// 单个文件的搜索统计
+#[derive(Debug, PartialEq)]
+pub struct FileStats {
+    pub filename: String,
+    pub bytes: usize,
+    /// 匹配的行数(CSV/TSV 为记录数), 一行中有多处匹配也只算一次
+    pub matching_lines: usize,
+    /// 匹配的次数, 一行中的每一处匹配都算一次
+    pub matches: usize,
+}
+
+/// 一次搜索的统计信息, 用于判断查询条件是否过于宽泛
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub files: Vec<FileStats>,
+    pub elapsed: Duration,
+}
+
+impl Stats {
+    pub fn add_file(
+        &mut self,
+        filename: &str,
+        bytes: usize,
+        matching_lines: usize,
+        matches: usize,
+    ) {
+        self.files.push(FileStats {
+            filename: filename.to_string(),
+            bytes,
+            matching_lines,
+            matches,
+        });
+    }
+
+    pub fn files_searched(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn bytes_scanned(&self) -> usize {
+        self.files.iter().map(|file| file.bytes).sum()
+    }
+
+    pub fn lines_matched(&self) -> usize {
+        self.files.iter().map(|file| file.matching_lines).sum()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "搜索文件数: {}", self.files_searched())?;
+        writeln!(f, "扫描字节数: {}", self.bytes_scanned())?;
+        writeln!(f, "匹配行数: {}", self.lines_matched())?;
+        for file in &self.files {
+            writeln!(f, "  {}: {} 处匹配", file.filename, file.matches)?;
+        }
+        writeln!(f, "耗时: {:?}", self.elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals() {
+        let mut stats = Stats::default();
+        stats.add_file("a.txt", 100, 3, 4);
+        stats.add_file("b.txt", 20, 0, 0);
+
+        assert_eq!(2, stats.files_searched());
+        assert_eq!(120, stats.bytes_scanned());
+        assert_eq!(3, stats.lines_matched());
+    }
+
+    #[test]
+    fn report() {
+        let mut stats = Stats::default();
+        stats.add_file("poem.txt", 237, 2, 3);
+        stats.elapsed = Duration::from_millis(5);
+
+        assert_eq!(
+            "\
+搜索文件数: 1
+扫描字节数: 237
+匹配行数: 2
+  poem.txt: 3 处匹配
+耗时: 5ms
+",
+            stats.to_string()
+        );
+    }
+}
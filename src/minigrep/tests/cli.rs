@@ -0,0 +1,287 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    if name != "poem.txt" {
+        path.push("tests/fixtures");
+    }
+    path.push(name);
+    path.to_string_lossy().into_owned()
+}
+
+fn minigrep(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(args)
+        .env_remove("CASE_INSENSITIVE")
+        .output()
+        .expect("运行 minigrep 失败")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// 断言程序成功退出, 标准错误为空, 标准输出与 `expected` 相同
+fn assert_success(output: &Output, expected: &str) {
+    assert_eq!(expected, stdout(output));
+    assert_eq!("", stderr(output));
+    assert_eq!(Some(0), output.status.code());
+}
+
+/// 断言程序以 1 退出, 标准输出为空, 标准错误为 `expected` 加换行
+fn assert_failure(output: &Output, expected: &str) {
+    assert_eq!("", stdout(output));
+    assert_eq!(format!("{}\n", expected), stderr(output));
+    assert_eq!(Some(1), output.status.code());
+}
+
+#[test]
+fn search_poem() {
+    let output = minigrep(&["to", &fixture("poem.txt")]);
+
+    assert_success(
+        &output,
+        "Are you nobody, too?\nHow dreary to be somebody!\n",
+    );
+}
+
+#[test]
+fn no_match_prints_nothing() {
+    let output = minigrep(&["monomorphization", &fixture("poem.txt")]);
+
+    assert_success(&output, "");
+}
+
+#[test]
+fn case_insensitive_from_env() {
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(["to", &fixture("poem.txt")])
+        .env("CASE_INSENSITIVE", "1")
+        .output()
+        .unwrap();
+
+    assert_success(
+        &output,
+        "\
+Are you nobody, too?
+How dreary to be somebody!
+To tell your name the livelong day
+To an admiring bog!
+",
+    );
+}
+
+#[test]
+fn missing_query() {
+    assert_failure(&minigrep(&[]), "解析参数错误: 未获取到需要搜索的字符串");
+}
+
+#[test]
+fn missing_filename() {
+    assert_failure(&minigrep(&["to"]), "解析参数错误: 未获取到需要搜索的文件名");
+}
+
+#[test]
+fn unknown_option() {
+    assert_failure(
+        &minigrep(&["--color", "to", &fixture("poem.txt")]),
+        "解析参数错误: 未知的选项",
+    );
+}
+
+#[test]
+fn missing_file() {
+    let output = minigrep(&["to", &fixture("no-such-file.txt")]);
+
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).starts_with("程序错误: "));
+    assert_eq!(Some(1), output.status.code());
+}
+
+#[test]
+fn multiple_files_are_prefixed() {
+    let poem = fixture("poem.txt");
+    let csv = fixture("requests.csv");
+    let output = minigrep(&["in", &poem, &csv]);
+
+    assert_success(
+        &output,
+        &format!(
+            "\
+{poem}:To an admiring bog!
+{csv}:1,/index,200
+{csv}:3,/login,500
+{csv}:line\",404
+"
+        ),
+    );
+}
+
+#[test]
+fn csv_named_field() {
+    let output = minigrep(&["--csv", "--field", "status=500", &fixture("requests.csv")]);
+
+    assert_success(&output, "2,\"/search?q=a,b\",500\n3,/login,500\n");
+}
+
+#[test]
+fn csv_numbered_field() {
+    let output = minigrep(&[
+        "--csv",
+        "--field",
+        "2=/multi\nline",
+        &fixture("requests.csv"),
+    ]);
+
+    assert_success(&output, "4,\"/multi\nline\",404\n");
+}
+
+#[test]
+fn csv_field_is_exact() {
+    let output = minigrep(&["--csv", "--field", "status=50", &fixture("requests.csv")]);
+
+    assert_success(&output, "");
+}
+
+#[test]
+fn csv_header_is_never_matched() {
+    let csv = fixture("requests.csv");
+
+    assert_success(&minigrep(&["--csv", "--field", "3=status", &csv]), "");
+    assert_success(&minigrep(&["--csv", "path", &csv]), "");
+}
+
+#[test]
+fn csv_whole_record() {
+    let output = minigrep(&["--csv", "a,b", &fixture("requests.csv")]);
+
+    assert_success(&output, "2,\"/search?q=a,b\",500\n");
+}
+
+#[test]
+fn csv_unknown_field() {
+    assert_failure(
+        &minigrep(&["--csv", "--field", "code=500", &fixture("requests.csv")]),
+        "程序错误: 未找到列: code",
+    );
+}
+
+#[test]
+fn csv_unterminated_quote() {
+    assert_failure(
+        &minigrep(&["--csv", "ok", &fixture("broken.csv")]),
+        "程序错误: 第 2 行的引号未闭合",
+    );
+}
+
+#[test]
+fn tsv_named_field() {
+    let output = minigrep(&["--tsv", "--field", "path=/login", &fixture("requests.tsv")]);
+
+    assert_success(&output, "3\t/login\t500\n");
+}
+
+#[test]
+fn field_requires_format() {
+    assert_failure(
+        &minigrep(&["--field", "status=500", &fixture("requests.csv")]),
+        "解析参数错误: --field 需要配合 --csv 或 --tsv 使用",
+    );
+}
+
+#[test]
+fn field_requires_value() {
+    assert_failure(
+        &minigrep(&["--csv", "--field", "status", &fixture("requests.csv")]),
+        "解析参数错误: --field 的格式应为 列=值",
+    );
+}
+
+#[test]
+fn stats_report() {
+    let poem = fixture("poem.txt");
+    let output = minigrep(&["--stats", "to", &poem]);
+
+    assert_eq!(
+        "Are you nobody, too?\nHow dreary to be somebody!\n",
+        stdout(&output)
+    );
+    let report = stderr(&output);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(5, lines.len());
+    assert_eq!("搜索文件数: 1", lines[0]);
+    assert_eq!(
+        format!("扫描字节数: {}", std::fs::metadata(&poem).unwrap().len()),
+        lines[1]
+    );
+    assert_eq!("匹配行数: 2", lines[2]);
+    assert_eq!(format!("  {}: 2 处匹配", poem), lines[3]);
+    assert!(lines[4].starts_with("耗时: "));
+    assert_eq!(Some(0), output.status.code());
+}
+
+#[test]
+fn stats_count_lines_and_matches() {
+    let poem = fixture("poem.txt");
+    let csv = fixture("requests.csv");
+    let output = minigrep(&["--stats", "he", &poem, &csv]);
+
+    // "Then there's ..." 一行有两处匹配: 总数按行算, 每个文件按匹配次数算
+    let report = stderr(&output);
+    assert!(report.contains("匹配行数: 3\n"));
+    assert!(report.contains(&format!("  {}: 4 处匹配\n", poem)));
+    assert!(report.contains(&format!("  {}: 0 处匹配\n", csv)));
+}
+
+#[test]
+fn only_matching() {
+    let output = minigrep(&["--only-matching", "bod", &fixture("poem.txt")]);
+
+    assert_success(&output, "bod\nbod\nbod\n");
+}
+
+#[test]
+fn unique() {
+    let output = minigrep(&["--unique", "--only-matching", "o", &fixture("poem.txt")]);
+
+    assert_success(&output, "o\n");
+}
+
+#[test]
+fn top() {
+    let output = minigrep(&[
+        "--csv",
+        "--field",
+        "status=500",
+        "--only-matching",
+        "--top",
+        "1",
+        &fixture("requests.csv"),
+    ]);
+
+    assert_success(&output, "      2 500\n");
+}
+
+#[test]
+fn top_lines_across_files() {
+    let poem = fixture("poem.txt");
+    let output = minigrep(&["--top", "3", "o", &poem, &poem, &fixture("requests.csv")]);
+
+    assert_success(
+        &output,
+        "      2 Are you nobody, too?\n      2 How dreary to be somebody!\n      2 How public, like a frog\n",
+    );
+}
+
+#[test]
+fn top_requires_number() {
+    assert_failure(
+        &minigrep(&["--top", "ten", "to", &fixture("poem.txt")]),
+        "解析参数错误: --top 需要一个数字参数",
+    );
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// 教程各个阶段的可执行文件
+const VERSIONS: [(&str, &str); 8] = [
+    ("v1", env!("CARGO_BIN_EXE_minigrep_v1")),
+    ("v2", env!("CARGO_BIN_EXE_minigrep_v2")),
+    ("v3", env!("CARGO_BIN_EXE_minigrep_v3")),
+    ("v4", env!("CARGO_BIN_EXE_minigrep_v4")),
+    ("v5", env!("CARGO_BIN_EXE_minigrep_v5")),
+    ("v6", env!("CARGO_BIN_EXE_minigrep_v6")),
+    ("v7", env!("CARGO_BIN_EXE_minigrep_v7")),
+    ("v8", env!("CARGO_BIN_EXE_minigrep_v8")),
+];
+
+fn run(exe: &str, args: &[&str]) -> Output {
+    Command::new(exe)
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env_remove("CASE_INSENSITIVE")
+        .output()
+        .expect("运行程序失败")
+}
+
+fn read(path: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(path)).unwrap()
+}
+
+#[test]
+fn each_version_matches_recorded_output() {
+    for (version, exe) in VERSIONS {
+        let output = run(exe, &["to", "poem.txt"]);
+
+        // 前几版会打印出参数列表, 其中的程序路径因机器而异
+        let stdout = String::from_utf8(output.stdout)
+            .unwrap()
+            .replace(exe, "{exe}");
+        let expected = read(&format!("tests/fixtures/legacy/{}.txt", version));
+
+        assert_eq!(expected, stdout, "{} 的输出与记录不一致", version);
+        assert_eq!(Some(0), output.status.code(), "{}", version);
+    }
+}
+
+#[test]
+fn v1_to_v4_panic_without_args() {
+    for (version, exe) in &VERSIONS[..4] {
+        let output = run(exe, &[]);
+
+        assert!(output.stdout.is_empty(), "{}", version);
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .contains("index out of bounds"),
+            "{}",
+            version
+        );
+        assert_eq!(Some(101), output.status.code(), "{}", version);
+    }
+}
+
+#[test]
+fn v5_to_v7_report_errors_on_stdout() {
+    for (version, exe) in &VERSIONS[4..7] {
+        let output = run(exe, &[]);
+
+        assert_eq!(
+            "解析参数错误: 参数不够\n",
+            String::from_utf8(output.stdout).unwrap(),
+            "{}",
+            version
+        );
+        assert!(output.stderr.is_empty(), "{}", version);
+        assert_eq!(Some(1), output.status.code(), "{}", version);
+    }
+}
+
+/// `output.txt` 是 `cargo run > output.txt` 的结果: 从第 8 版起错误信息输出到标准错误, 标准输出为空
+#[test]
+fn v8_and_current_report_errors_on_stderr() {
+    let recorded = read("output.txt");
+
+    for (exe, message) in [
+        (VERSIONS[7].1, "解析参数错误: 参数不够\n"),
+        (
+            env!("CARGO_BIN_EXE_minigrep"),
+            "解析参数错误: 未获取到需要搜索的字符串\n",
+        ),
+    ] {
+        let output = run(exe, &[]);
+
+        assert_eq!(recorded, String::from_utf8(output.stdout).unwrap());
+        assert_eq!(message, String::from_utf8(output.stderr).unwrap());
+        assert_eq!(Some(1), output.status.code());
+    }
+}
+
+#[test]
+fn current_version_matches_v8_search() {
+    let output = run(env!("CARGO_BIN_EXE_minigrep"), &["to", "poem.txt"]);
+
+    assert_eq!(
+        read("tests/fixtures/legacy/v8.txt").as_bytes(),
+        &output.stdout[..]
+    );
+}
@@ -0,0 +1,256 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use List::{Back, Cons, Nil};
+
+/// 尾部可以修改的链表, 修改尾部可能产生引用循环
+///
+/// `Back` 是被 [`break_cycle`] 降级为弱引用的链接: 它仍然指向原来的节点, 但不再持有它.
+pub enum List {
+    Cons(i32, RefCell<Rc<List>>),
+    Back(Weak<List>),
+    Nil,
+}
+
+/// 沿着链接走到的下一步
+enum Step {
+    Next(Rc<List>),
+    End,
+    Dropped,
+}
+
+impl List {
+    pub fn cons(value: i32, next: &Rc<List>) -> Rc<List> {
+        Rc::new(Cons(value, RefCell::new(Rc::clone(next))))
+    }
+
+    pub fn nil() -> Rc<List> {
+        Rc::new(Nil)
+    }
+
+    pub fn tail(&self) -> Option<&RefCell<Rc<List>>> {
+        match self {
+            Cons(_, item) => Some(item),
+            _ => None,
+        }
+    }
+
+    fn step(&self) -> Step {
+        match self {
+            Cons(_, link) => Step::Next(Rc::clone(&link.borrow())),
+            Back(target) => match target.upgrade() {
+                Some(target) => Step::Next(target),
+                None => Step::Dropped,
+            },
+            Nil => Step::End,
+        }
+    }
+}
+
+impl fmt::Debug for List {
+    // 逐个节点输出而不递归, 再次遇到同一个节点时输出 <cycle>
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut seen = HashSet::new();
+        let mut open = 0;
+        let mut current: Option<Rc<List>> = None;
+
+        loop {
+            let node = current.as_deref().unwrap_or(self);
+            if !seen.insert(node as *const List) {
+                write!(f, "<cycle>")?;
+                break;
+            }
+
+            match node {
+                Cons(value, _) => write!(f, "Cons({}, ", value)?,
+                Back(_) => write!(f, "Back(")?,
+                Nil => {
+                    write!(f, "Nil")?;
+                    break;
+                }
+            }
+            open += 1;
+
+            match node.step() {
+                Step::Next(next) => current = Some(next),
+                Step::Dropped => {
+                    write!(f, "<dropped>")?;
+                    break;
+                }
+                Step::End => break,
+            }
+        }
+
+        for _ in 0..open {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// 依次返回链表中的值, 回到已经访问过的节点时停止
+pub struct Iter {
+    next: Option<Rc<List>>,
+    seen: HashSet<*const List>,
+}
+
+pub fn iter(list: &Rc<List>) -> Iter {
+    Iter {
+        next: Some(Rc::clone(list)),
+        seen: HashSet::new(),
+    }
+}
+
+impl Iterator for Iter {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        while let Some(node) = self.next.take() {
+            if !self.seen.insert(Rc::as_ptr(&node)) {
+                return None;
+            }
+
+            if let Step::Next(next) = node.step() {
+                self.next = Some(next);
+            }
+            if let Cons(value, _) = *node {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+/// 找到指回已访问节点的那个节点, 即闭合引用循环的位置
+///
+/// 只沿强引用查找, `Back` 链接不会造成泄漏, 不算作循环.
+pub fn find_cycle(list: &Rc<List>) -> Option<Rc<List>> {
+    let mut seen = HashSet::new();
+    let mut current = Rc::clone(list);
+
+    loop {
+        seen.insert(Rc::as_ptr(&current));
+        let next = match &*current {
+            Cons(_, link) => Rc::clone(&link.borrow()),
+            _ => return None,
+        };
+        if seen.contains(&Rc::as_ptr(&next)) {
+            return Some(current);
+        }
+        current = next;
+    }
+}
+
+pub fn has_cycle(list: &Rc<List>) -> bool {
+    find_cycle(list).is_some()
+}
+
+/// 把闭合循环的链接降级为弱引用, 返回是否发现了循环
+pub fn break_cycle(list: &Rc<List>) -> bool {
+    match find_cycle(list) {
+        Some(node) => {
+            if let Some(link) = node.tail() {
+                let target = Rc::downgrade(&link.borrow());
+                *link.borrow_mut() = Rc::new(Back(target));
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a -> b -> a
+    fn two_node_cycle() -> (Rc<List>, Rc<List>) {
+        let a = List::cons(5, &List::nil());
+        let b = List::cons(10, &a);
+        *a.tail().unwrap().borrow_mut() = Rc::clone(&b);
+        (a, b)
+    }
+
+    #[test]
+    fn debug_without_cycle() {
+        let list = List::cons(1, &List::cons(2, &List::nil()));
+
+        assert_eq!("Cons(1, Cons(2, Nil))", format!("{:?}", list));
+        assert!(!has_cycle(&list));
+        assert!(!break_cycle(&list));
+    }
+
+    #[test]
+    fn debug_prints_cycle_marker() {
+        let (a, b) = two_node_cycle();
+
+        assert_eq!("Cons(5, Cons(10, <cycle>))", format!("{:?}", a));
+        assert_eq!(
+            "Some(RefCell { value: Cons(10, Cons(5, <cycle>)) })",
+            format!("{:?}", a.tail())
+        );
+        assert_eq!("Cons(10, Cons(5, <cycle>))", format!("{:?}", b));
+
+        break_cycle(&a);
+    }
+
+    #[test]
+    fn self_cycle() {
+        let a = List::cons(1, &List::nil());
+        *a.tail().unwrap().borrow_mut() = Rc::clone(&a);
+
+        assert_eq!("Cons(1, <cycle>)", format!("{:?}", a));
+        assert!(Rc::ptr_eq(&a, &find_cycle(&a).unwrap()));
+
+        assert!(break_cycle(&a));
+        assert_eq!("Cons(1, Back(<cycle>))", format!("{:?}", a));
+        assert_eq!(1, Rc::strong_count(&a));
+    }
+
+    #[test]
+    fn iteration_stops_at_cycle() {
+        let (a, b) = two_node_cycle();
+        let c = List::cons(1, &a);
+
+        assert_eq!(vec![1, 5, 10], iter(&c).collect::<Vec<_>>());
+        assert!(Rc::ptr_eq(&b, &find_cycle(&c).unwrap()));
+
+        break_cycle(&c);
+        assert_eq!(vec![1, 5, 10], iter(&c).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn breaking_cycle_frees_nodes() {
+        let (a, b) = two_node_cycle();
+        let (weak_a, weak_b) = (Rc::downgrade(&a), Rc::downgrade(&b));
+
+        assert_eq!(2, Rc::strong_count(&a));
+        assert!(break_cycle(&a));
+        assert!(!has_cycle(&a));
+        assert_eq!(1, Rc::strong_count(&a));
+        assert_eq!("Cons(5, Cons(10, Back(<cycle>)))", format!("{:?}", a));
+
+        drop(a);
+        assert!(weak_a.upgrade().is_none());
+        assert_eq!("Cons(10, Back(<dropped>))", format!("{:?}", b));
+
+        drop(b);
+        assert!(weak_b.upgrade().is_none());
+    }
+
+    #[test]
+    fn unbroken_cycle_leaks() {
+        let (a, b) = two_node_cycle();
+        let weak_a = Rc::downgrade(&a);
+
+        drop(a);
+        drop(b);
+        assert!(weak_a.upgrade().is_some());
+
+        // 清理, 以免测试本身泄漏
+        break_cycle(&weak_a.upgrade().unwrap());
+        assert!(weak_a.upgrade().is_none());
+    }
+}
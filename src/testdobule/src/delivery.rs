@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use crate::{Messenger, Severity};
+
+/// 发送失败时的重试策略: 最多尝试 `attempts` 次, 每次失败后等待的时间翻倍
+///
+/// 等待发生在调用 `set_value` 等方法的线程中, 会阻塞调用方. 默认只尝试一次、不等待,
+/// 失败的消息进入死信队列, 由调用方在合适的时候(例如定时任务中)调用 `redeliver` 重发.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retry {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+impl Retry {
+    pub fn new(attempts: u32, backoff: Duration) -> Retry {
+        Retry { attempts, backoff }
+    }
+
+    /// 只尝试一次, 不重试
+    pub fn none() -> Retry {
+        Retry::new(1, Duration::ZERO)
+    }
+}
+
+impl Default for Retry {
+    fn default() -> Retry {
+        Retry::none()
+    }
+}
+
+/// 重试之后仍然没有发送出去的消息
+#[derive(Debug)]
+pub struct DeadLetter {
+    pub severity: Severity,
+    pub message: String,
+    pub error: io::Error,
+}
+
+/// 按照 `retry` 发送一条消息, 全部失败时返回最后一次的错误
+///
+/// 重试之间用 `thread::sleep` 等待, `retry.backoff` 不为零时会阻塞当前线程.
+/// 重试通过 `Messenger::resend_alert` 进行, 已经成功的部分不会重复发送.
+pub fn deliver<T: Messenger + ?Sized>(
+    messenger: &T,
+    retry: Retry,
+    severity: Severity,
+    msg: &str,
+) -> Result<(), DeadLetter> {
+    let error = match messenger.send_alert(severity, msg) {
+        Ok(()) => return Ok(()),
+        Err(error) => error,
+    };
+    let letter = DeadLetter {
+        severity,
+        message: String::from(msg),
+        error,
+    };
+
+    if retry.attempts <= 1 {
+        return Err(letter);
+    }
+    thread::sleep(retry.backoff);
+    resend(
+        messenger,
+        Retry::new(retry.attempts - 1, retry.backoff * 2),
+        letter,
+    )
+}
+
+/// 按照 `retry` 重发一条死信, 每次都把上一次的错误交给 `resend_alert`
+fn resend<T: Messenger + ?Sized>(
+    messenger: &T,
+    retry: Retry,
+    mut letter: DeadLetter,
+) -> Result<(), DeadLetter> {
+    let mut backoff = retry.backoff;
+
+    for attempt in 1..=retry.attempts.max(1) {
+        if attempt > 1 {
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+        match messenger.resend_alert(letter.severity, &letter.message, &letter.error) {
+            Ok(()) => return Ok(()),
+            Err(error) => letter.error = error,
+        }
+    }
+
+    Err(letter)
+}
+
+/// 有容量上限的死信队列, 满了以后丢弃最旧的消息
+#[derive(Debug)]
+pub struct DeadLetters {
+    capacity: usize,
+    letters: VecDeque<DeadLetter>,
+}
+
+impl DeadLetters {
+    pub fn new(capacity: usize) -> DeadLetters {
+        DeadLetters {
+            capacity,
+            letters: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, letter: DeadLetter) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.letters.len() == self.capacity {
+            self.letters.pop_front();
+        }
+        self.letters.push_back(letter);
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DeadLetter> {
+        self.letters.iter()
+    }
+
+    pub fn take(&mut self) -> Vec<DeadLetter> {
+        self.letters.drain(..).collect()
+    }
+
+    /// 重新发送所有死信, 返回成功发送的数量, 仍然失败的留在队列中
+    pub fn redeliver<T: Messenger + ?Sized>(&mut self, messenger: &T, retry: Retry) -> usize {
+        let mut delivered = 0;
+
+        for letter in self.take() {
+            match resend(messenger, retry, letter) {
+                Ok(()) => delivered += 1,
+                Err(letter) => self.push(letter),
+            }
+        }
+
+        delivered
+    }
+}
+
+impl Default for DeadLetters {
+    fn default() -> DeadLetters {
+        DeadLetters::new(100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockMessenger;
+
+    /// 前 `failures` 次发送失败, 之后成功
+    fn flaky(failures: usize) -> MockMessenger {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(failures, "unavailable");
+        messenger
+    }
+
+    #[test]
+    fn default_policy_never_waits() {
+        let messenger = flaky(1);
+
+        assert_eq!(Retry::none(), Retry::default());
+        assert!(deliver(&messenger, Retry::default(), Severity::Info, "hi").is_err());
+        assert_eq!(1, messenger.calls.call_count());
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let messenger = flaky(2);
+
+        let result = deliver(
+            &messenger,
+            Retry::new(3, Duration::ZERO),
+            Severity::Info,
+            "hi",
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(3, messenger.calls.call_count());
+    }
+
+    #[test]
+    fn gives_up_after_all_attempts() {
+        let messenger = flaky(5);
+
+        let letter = deliver(
+            &messenger,
+            Retry::new(3, Duration::ZERO),
+            Severity::Error,
+            "hi",
+        )
+        .unwrap_err();
+
+        assert_eq!(3, messenger.calls.call_count());
+        assert_eq!(Severity::Error, letter.severity);
+        assert_eq!("hi", letter.message);
+        assert_eq!("unavailable", letter.error.to_string());
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        let messenger = flaky(2);
+        let start = std::time::Instant::now();
+
+        deliver(
+            &messenger,
+            Retry::new(3, Duration::from_millis(10)),
+            Severity::Info,
+            "hi",
+        )
+        .unwrap();
+
+        // 10ms + 20ms
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn dead_letters_drop_oldest() {
+        let mut letters = DeadLetters::new(2);
+
+        for message in ["a", "b", "c"] {
+            letters.push(DeadLetter {
+                severity: Severity::Info,
+                message: String::from(message),
+                error: io::Error::other("x"),
+            });
+        }
+
+        let messages: Vec<&str> = letters.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(vec!["b", "c"], messages);
+    }
+
+    #[test]
+    fn redeliver_keeps_failures() {
+        let messenger = flaky(1);
+        let mut letters = DeadLetters::default();
+        for message in ["a", "b"] {
+            letters.push(DeadLetter {
+                severity: Severity::Info,
+                message: String::from(message),
+                error: io::Error::other("x"),
+            });
+        }
+
+        assert_eq!(1, letters.redeliver(&messenger, Retry::none()));
+        assert_eq!(1, letters.len());
+        assert_eq!(1, letters.redeliver(&messenger, Retry::none()));
+        assert!(letters.is_empty());
+    }
+}
@@ -1,38 +1,99 @@
+use std::io;
+
+pub use delivery::{DeadLetter, DeadLetters, Retry};
+pub use list::{CellList, List};
+pub use policy::{Policy, Severity, Threshold};
+pub use registry::{QuotaRegistry, UnknownQuota};
+pub use sync::SyncLimitTracker;
+pub use window::{Clock, ManualClock, RateTracker, SystemClock};
+
+pub mod cyclic;
+pub mod delivery;
+pub mod list;
+pub mod messengers;
+pub mod mock;
+pub mod policy;
+pub mod registry;
+pub mod sync;
+pub mod tree;
+pub mod window;
+
 pub trait Messenger {
-    fn send(&self, msg: &str);
+    fn send(&self, msg: &str) -> io::Result<()>;
+
+    /// 带严重程度发送, 默认忽略严重程度直接调用 `send`
+    fn send_alert(&self, _severity: Severity, msg: &str) -> io::Result<()> {
+        self.send(msg)
+    }
+
+    /// 上一次发送同一条消息返回 `error` 之后重发, 默认直接调用 `send_alert`
+    ///
+    /// 可能部分成功的实现(例如 `FanOutMessenger`)可以根据 `error` 只重发失败的部分.
+    fn resend_alert(&self, severity: Severity, msg: &str, _error: &io::Error) -> io::Result<()> {
+        self.send_alert(severity, msg)
+    }
 }
 
 pub struct LimitTracker<'a, T: Messenger> {
     messenger: &'a T,
     value: usize,
     max: usize,
+    policy: Policy,
+    // 上一次告警所在的档, 只在档位变化时发送消息
+    alerted: Option<usize>,
+    retry: Retry,
+    dead_letters: DeadLetters,
 }
 
 impl<'a, T> LimitTracker<'a, T>
 where
     T: Messenger,
 {
-    pub fn new(messenger: &T, max: usize) -> LimitTracker<T> {
+    pub fn new(messenger: &'a T, max: usize) -> LimitTracker<'a, T> {
+        LimitTracker::with_policy(messenger, max, Policy::default())
+    }
+
+    pub fn with_policy(messenger: &'a T, max: usize, policy: Policy) -> LimitTracker<'a, T> {
         LimitTracker {
             messenger,
             value: 0,
             max,
+            policy,
+            alerted: None,
+            retry: Retry::default(),
+            dead_letters: DeadLetters::default(),
         }
     }
 
+    pub fn with_retry(mut self, retry: Retry) -> LimitTracker<'a, T> {
+        self.retry = retry;
+        self
+    }
+
+    /// 重试后仍未发送出去的消息
+    pub fn dead_letters(&self) -> &DeadLetters {
+        &self.dead_letters
+    }
+
+    /// 重新发送死信, 返回成功发送的数量
+    pub fn redeliver(&mut self) -> usize {
+        self.dead_letters.redeliver(self.messenger, self.retry)
+    }
+
     pub fn set_value(&mut self, value: usize) {
         self.value = value;
 
-        let percentage_of_max = self.value as f64 / self.max as f64;
+        let transition = self
+            .policy
+            .transition(&mut self.alerted, self.value, self.max);
 
-        if percentage_of_max >= 1.0 {
-            self.messenger.send("Error: You are over your quota!");
-        } else if percentage_of_max >= 0.9 {
-            self.messenger
-                .send("Urgent warning: You've used up over 90% of your quota!");
-        } else if percentage_of_max >= 0.75 {
-            self.messenger
-                .send("Warning: You've used up over 75% of your quota!");
+        if let Some(threshold) = transition {
+            let message = threshold.render(self.value, self.max);
+            if let Err(letter) =
+                delivery::deliver(self.messenger, self.retry, threshold.severity, &message)
+            {
+                self.dead_letters.push(letter);
+            }
         }
     }
 }
@@ -40,33 +101,196 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::cell::RefCell;
+    use mock::{alert, any, call, contains, eq, MockMessenger};
+    use std::time::Duration;
+
+    #[test]
+    fn it_sends_an_over_75_percent_warning_message() {
+        let mock_messenger = MockMessenger::new();
+        mock_messenger
+            .calls
+            .expect(call(alert(any(), contains("over 75%"))));
+        let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
+
+        limit_tracker.set_value(80);
 
-    struct MockMessenger {
-        sent_messages: RefCell<Vec<String>>,
+        assert_eq!(mock_messenger.calls.call_count(), 1);
+        mock_messenger.calls.verify();
     }
 
-    impl MockMessenger {
-        fn new() -> MockMessenger {
-            MockMessenger {
-                sent_messages: RefCell::new(vec![]),
-            }
-        }
+    #[test]
+    fn default_policy_messages() {
+        let mock_messenger = MockMessenger::new();
+        mock_messenger
+            .calls
+            .expect(call(alert(eq(Severity::Warning), any())).never());
+        mock_messenger.calls.expect(
+            call(alert(
+                eq(Severity::Urgent),
+                eq(String::from(
+                    "Urgent warning: You've used up over 90% of your quota!",
+                )),
+            ))
+            .in_order(),
+        );
+        mock_messenger.calls.expect(
+            call(alert(
+                eq(Severity::Error),
+                eq(String::from("Error: You are over your quota!")),
+            ))
+            .in_order(),
+        );
+        let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
+
+        limit_tracker.set_value(10);
+        limit_tracker.set_value(90);
+        limit_tracker.set_value(100);
+
+        assert_eq!(mock_messenger.calls.call_count(), 2);
+        mock_messenger.calls.verify();
     }
 
-    impl Messenger for MockMessenger {
-        fn send(&self, message: &str) {
-            self.sent_messages.borrow_mut().push(String::from(message));
-        }
+    #[test]
+    fn custom_policy_with_severity() {
+        let messenger = MockMessenger::new();
+        let policy = Policy::new(vec![
+            Threshold::new(50, Severity::Warning, "half used: {value}/{max}"),
+            Threshold::new(95, Severity::Error, "almost out: {value}/{max}"),
+        ]);
+        let mut limit_tracker = LimitTracker::with_policy(&messenger, 200, policy);
+
+        limit_tracker.set_value(99);
+        limit_tracker.set_value(100);
+        limit_tracker.set_value(190);
+
+        assert_eq!(
+            vec![
+                (Severity::Warning, String::from("half used: 100/200")),
+                (Severity::Error, String::from("almost out: 190/200")),
+            ],
+            messenger.calls.calls()
+        );
     }
 
     #[test]
-    fn it_sends_an_over_75_percent_warning_message() {
+    fn zero_quota_never_alerts() {
+        let messenger = MockMessenger::new();
+        let mut limit_tracker = LimitTracker::new(&messenger, 0);
+
+        limit_tracker.set_value(0);
+        limit_tracker.set_value(5);
+
+        assert_eq!(0, messenger.calls.call_count());
+    }
+
+    #[test]
+    fn it_does_not_repeat_an_alert_for_the_same_tier() {
         let mock_messenger = MockMessenger::new();
+        mock_messenger.calls.expect(call(any()).times(1));
         let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
 
         limit_tracker.set_value(80);
+        limit_tracker.set_value(82);
+        limit_tracker.set_value(78);
+
+        mock_messenger.calls.verify();
+    }
+
+    #[test]
+    fn it_alerts_on_downgrade_and_recovery() {
+        let messenger = MockMessenger::new();
+        let policy = Policy::default().with_hysteresis(5);
+        let mut limit_tracker = LimitTracker::with_policy(&messenger, 100, policy);
+
+        for value in [95, 88, 86, 84, 72, 70, 69, 80] {
+            limit_tracker.set_value(value);
+        }
+
+        assert_eq!(
+            vec![
+                Severity::Urgent,  // 95
+                Severity::Warning, // 84
+                Severity::Info,    // 69
+                Severity::Warning, // 80
+            ],
+            messenger.severities()
+        );
+        assert_eq!(
+            "Recovered: You are back below 75% of your quota.",
+            messenger.messages()[2]
+        );
+    }
+
+    #[test]
+    fn failed_delivery_is_retried_then_dead_lettered() {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(3, "service unavailable");
+        let mut limit_tracker =
+            LimitTracker::new(&messenger, 100).with_retry(Retry::new(3, Duration::ZERO));
+
+        limit_tracker.set_value(80);
+
+        assert_eq!(3, messenger.calls.call_count());
+
+        let letters: Vec<&DeadLetter> = limit_tracker.dead_letters().iter().collect();
+        assert_eq!(1, letters.len());
+        assert_eq!(Severity::Warning, letters[0].severity);
+        assert_eq!(
+            "Warning: You've used up over 75% of your quota!",
+            letters[0].message
+        );
+        assert_eq!("service unavailable", letters[0].error.to_string());
+    }
+
+    #[test]
+    fn default_tracker_does_not_retry() {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(1, "service unavailable");
+        let mut limit_tracker = LimitTracker::new(&messenger, 100);
+
+        limit_tracker.set_value(80);
+
+        // 不在 set_value 中等待重试, 失败的消息留给调用方 redeliver
+        assert_eq!(1, messenger.calls.call_count());
+        assert_eq!(1, limit_tracker.dead_letters().len());
+        assert_eq!(1, limit_tracker.redeliver());
+    }
+
+    #[test]
+    fn dead_letters_are_redelivered_after_recovery() {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(4, "service unavailable");
+        let mut limit_tracker = LimitTracker::new(&messenger, 100).with_retry(Retry::none());
+
+        limit_tracker.set_value(80);
+        limit_tracker.set_value(100);
+        assert_eq!(2, limit_tracker.dead_letters().len());
+        assert_eq!(0, limit_tracker.redeliver());
+        assert_eq!(2, limit_tracker.dead_letters().len());
+
+        // 前四次失败之后服务恢复
+        assert_eq!(2, limit_tracker.redeliver());
+        assert!(limit_tracker.dead_letters().is_empty());
+        assert_eq!(
+            vec![
+                "Warning: You've used up over 75% of your quota!",
+                "Error: You are over your quota!",
+            ],
+            messenger.messages()[4..]
+        );
+    }
+
+    #[test]
+    fn a_failure_does_not_resend_the_same_tier() {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(1, "service unavailable");
+        let mut limit_tracker = LimitTracker::new(&messenger, 100).with_retry(Retry::none());
+
+        limit_tracker.set_value(80);
+        limit_tracker.set_value(81);
 
-        assert_eq!(mock_messenger.sent_messages.borrow().len(), 1);
+        // 失败的告警进入死信队列, 档位没有变化就不会再发
+        assert_eq!(1, messenger.calls.call_count());
+        assert_eq!(1, limit_tracker.dead_letters().len());
     }
 }
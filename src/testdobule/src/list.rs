@@ -0,0 +1,272 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+struct Node<T> {
+    value: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+/// 不可变的单链表(cons list), 多个链表可以共享同一段尾部
+///
+/// `push_front` 不修改原链表, 而是返回一个以原链表为尾部的新链表, 复制只需要增加引用计数.
+pub struct List<T> {
+    head: Option<Rc<Node<T>>>,
+    len: usize,
+}
+
+/// 元素可以修改的链表: 共享同一段尾部的链表都能看到修改
+pub type CellList<T> = List<RefCell<T>>;
+
+impl<T> List<T> {
+    pub fn new() -> List<T> {
+        List { head: None, len: 0 }
+    }
+
+    /// 返回在头部加上 `value` 的新链表, 原链表成为它的尾部
+    pub fn push_front(&self, value: T) -> List<T> {
+        List {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// 去掉第一个元素后的链表, 与原链表共享节点; 空链表的尾部还是空链表
+    pub fn tail(&self) -> List<T> {
+        match &self.head {
+            Some(node) => List {
+                head: node.next.clone(),
+                len: self.len - 1,
+            },
+            None => List::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    /// 两个链表是否是同一串节点(而不只是元素相等)
+    pub fn ptr_eq(&self, other: &List<T>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// 头节点被多少个链表引用, 空链表为 0
+    pub fn head_count(&self) -> usize {
+        self.head.as_ref().map_or(0, Rc::strong_count)
+    }
+}
+
+impl<T> List<RefCell<T>> {
+    pub fn push_front_cell(&self, value: T) -> List<RefCell<T>> {
+        self.push_front(RefCell::new(value))
+    }
+
+    /// 复制出当前元素值组成的普通链表
+    pub fn snapshot(&self) -> List<T>
+    where
+        T: Clone,
+    {
+        self.iter().map(|cell| cell.borrow().clone()).collect()
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> List<T> {
+        List::new()
+    }
+}
+
+impl<T> Clone for List<T> {
+    fn clone(&self) -> List<T> {
+        List {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    // 默认的递归析构在很长的链表上会栈溢出, 这里逐个释放不再被共享的节点
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    /// 元素顺序与迭代顺序相同
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> List<T> {
+        let values: Vec<T> = iter.into_iter().collect();
+        values
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, value| list.push_front(value))
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &List<T>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_front_and_iterate() {
+        let list = List::new().push_front(3).push_front(2).push_front(1);
+
+        assert_eq!(3, list.len());
+        assert_eq!(Some(&1), list.head());
+        assert_eq!(vec![1, 2, 3], list.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty_list() {
+        let list: List<i32> = List::new();
+
+        assert!(list.is_empty());
+        assert_eq!(None, list.head());
+        assert!(list.tail().is_empty());
+        assert_eq!("()", list.to_string());
+    }
+
+    #[test]
+    fn shared_tails() {
+        let a: List<i32> = [5, 10].into_iter().collect();
+        let b = a.push_front(3);
+        let c = a.push_front(4);
+
+        assert!(b.tail().ptr_eq(&a));
+        assert!(c.tail().ptr_eq(&a));
+        assert!(!b.ptr_eq(&c));
+        // a、b、c 都引用 a 的头节点
+        assert_eq!(3, a.head_count());
+
+        drop(a);
+        let shared = c.tail();
+        assert_eq!(3, shared.head_count());
+        assert_eq!("(3 5 10)", b.to_string());
+        assert_eq!("(4 5 10)", c.to_string());
+
+        // 释放 b 只释放它自己的头节点, 共享的尾部仍然存在
+        drop(b);
+        assert_eq!(2, shared.head_count());
+        assert_eq!("(5 10)", shared.to_string());
+    }
+
+    #[test]
+    fn from_iterator_keeps_order() {
+        let list: List<char> = "abc".chars().collect();
+
+        assert_eq!("(a b c)", list.to_string());
+        assert_eq!("['a', 'b', 'c']", format!("{:?}", list));
+    }
+
+    #[test]
+    fn equality_compares_values() {
+        let a: List<i32> = (1..4).collect();
+        let b = List::new().push_front(3).push_front(2).push_front(1);
+
+        assert_eq!(a, b);
+        assert!(!a.ptr_eq(&b));
+        assert_ne!(a, b.tail());
+    }
+
+    #[test]
+    fn cell_values_are_visible_through_shared_tails() {
+        let a: CellList<i32> = List::new().push_front_cell(5);
+        let b = a.push_front_cell(3);
+        let c = a.push_front_cell(4);
+
+        *a.head().unwrap().borrow_mut() += 10;
+
+        assert_eq!("(15)", a.snapshot().to_string());
+        assert_eq!("(3 15)", b.snapshot().to_string());
+        assert_eq!("(4 15)", c.snapshot().to_string());
+    }
+
+    #[test]
+    fn long_lists_drop_without_overflow() {
+        let list: List<u32> = (0..200_000).collect();
+        let shared = list.tail();
+
+        drop(list);
+        assert_eq!(199_999, shared.len());
+    }
+}
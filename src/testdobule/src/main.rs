@@ -1,44 +1,9 @@
-use std::cell::RefCell;
-use std::mem::drop;
-use std::rc::{Rc, Weak};
+use std::rc::Rc;
 
-#[derive(Debug)]
-struct Node {
-    value: i32,
-    parent: RefCell<Weak<Node>>,
-    children: RefCell<Vec<Rc<Node>>>,
-}
-
-fn mainv1() {
-    let leaf = Rc::new(Node {
-        value: 3,
-        parent: RefCell::new(Weak::new()),
-        children: RefCell::new(vec![]),
-    });
-
-    println!("leaf parent = {:?}", leaf.parent.borrow().upgrade());
-
-    let branch = Rc::new(Node {
-        value: 5,
-        parent: RefCell::new(Weak::new()),
-        children: RefCell::new(vec![Rc::clone(&leaf)]),
-    });
-
-    *leaf.parent.borrow_mut() = Rc::downgrade(&branch);
-
-    println!("leaf parent = {:?}", leaf.parent.borrow().upgrade());
-
-    std::mem::drop(branch);
-
-    println!("leaf parent = {:?}", leaf.parent.borrow().upgrade());
-}
+use testdobule::tree::Node;
 
 fn main() {
-    let leaf = Rc::new(Node {
-        value: 3,
-        parent: RefCell::new(Weak::new()),
-        children: RefCell::new(vec![]),
-    });
+    let leaf = Node::new(3);
 
     println!(
         "leaf strong = {}, weak = {}",
@@ -47,13 +12,8 @@ fn main() {
     );
 
     {
-        let branch = Rc::new(Node {
-            value: 5,
-            parent: RefCell::new(Weak::new()),
-            children: RefCell::new(vec![Rc::clone(&leaf)]),
-        });
-
-        *leaf.parent.borrow_mut() = Rc::downgrade(&branch);
+        let branch = Node::new(5);
+        branch.add_child(Rc::clone(&leaf)).unwrap();
 
         println!(
             "\nbranch strong = {}, weak = {}",
@@ -68,7 +28,7 @@ fn main() {
         );
     }
 
-    println!("leaf parent = {:?}", leaf.parent.borrow().upgrade());
+    println!("leaf parent = {:?}", leaf.parent());
     println!(
         "leaf strong = {}, weak = {}",
         Rc::strong_count(&leaf),
@@ -0,0 +1,9 @@
+pub use fanout::{Failure, FanOutError, FanOutMessenger};
+pub use file::FileMessenger;
+pub use http::HttpMessenger;
+pub use log::LogMessenger;
+
+pub mod fanout;
+pub mod file;
+pub mod http;
+pub mod log;
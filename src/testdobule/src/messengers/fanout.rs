@@ -0,0 +1,261 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::{Messenger, Severity};
+
+/// 某个后端投递失败的记录
+#[derive(Debug)]
+pub struct Failure {
+    pub backend: String,
+    pub error: io::Error,
+}
+
+/// `FanOutMessenger` 作为 `Messenger` 发送失败时, 包装在 `io::Error` 中的错误
+///
+/// 重发时只会发给其中记录的后端.
+#[derive(Debug)]
+pub struct FanOutError {
+    pub failures: Vec<Failure>,
+}
+
+impl fmt::Display for FanOutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let report: Vec<String> = self
+            .failures
+            .iter()
+            .map(|failure| format!("{}: {}", failure.backend, failure.error))
+            .collect();
+        f.write_str(&report.join("; "))
+    }
+}
+
+impl Error for FanOutError {}
+
+/// 把每条消息转发给多个后端, 一个后端失败不影响其他后端
+///
+/// 重试和 `redeliver` 只发给上一次失败的后端, 已经收到的后端不会收到重复的消息.
+pub struct FanOutMessenger {
+    backends: Vec<(String, Box<dyn Messenger + Send + Sync>)>,
+}
+
+impl FanOutMessenger {
+    pub fn new() -> FanOutMessenger {
+        FanOutMessenger { backends: vec![] }
+    }
+
+    pub fn add(
+        mut self,
+        name: &str,
+        backend: impl Messenger + Send + Sync + 'static,
+    ) -> FanOutMessenger {
+        self.backends.push((String::from(name), Box::new(backend)));
+        self
+    }
+
+    /// 发送给所有后端, 返回每个失败后端的错误
+    pub fn broadcast(&self, severity: Severity, msg: &str) -> Vec<Failure> {
+        self.send_to(|_| true, severity, msg)
+    }
+
+    /// 只发送给名字满足 `include` 的后端
+    fn send_to(
+        &self,
+        include: impl Fn(&str) -> bool,
+        severity: Severity,
+        msg: &str,
+    ) -> Vec<Failure> {
+        self.backends
+            .iter()
+            .filter(|(name, _)| include(name))
+            .filter_map(|(name, backend)| {
+                backend
+                    .send_alert(severity, msg)
+                    .err()
+                    .map(|error| Failure {
+                        backend: name.clone(),
+                        error,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl Default for FanOutMessenger {
+    fn default() -> FanOutMessenger {
+        FanOutMessenger::new()
+    }
+}
+
+impl Messenger for FanOutMessenger {
+    fn send(&self, msg: &str) -> io::Result<()> {
+        self.send_alert(Severity::Info, msg)
+    }
+
+    fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+        into_result(self.broadcast(severity, msg))
+    }
+
+    fn resend_alert(&self, severity: Severity, msg: &str, error: &io::Error) -> io::Result<()> {
+        let failed = error
+            .get_ref()
+            .and_then(|error| error.downcast_ref::<FanOutError>());
+
+        match failed {
+            Some(failed) => into_result(self.send_to(
+                |name| {
+                    failed
+                        .failures
+                        .iter()
+                        .any(|failure| failure.backend == name)
+                },
+                severity,
+                msg,
+            )),
+            None => self.send_alert(severity, msg),
+        }
+    }
+}
+
+fn into_result(failures: Vec<Failure>) -> io::Result<()> {
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(io::Error::other(FanOutError { failures }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LimitTracker, Retry};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Messenger for Recorder {
+        fn send(&self, msg: &str) -> io::Result<()> {
+            self.send_alert(Severity::Info, msg)
+        }
+
+        fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+            self.0.lock().unwrap().push(format!("{} {}", severity, msg));
+            Ok(())
+        }
+    }
+
+    struct Broken;
+
+    impl Messenger for Broken {
+        fn send(&self, _msg: &str) -> io::Result<()> {
+            Err(io::Error::other("connection refused"))
+        }
+    }
+
+    /// 前 `failures` 次发送失败, 之后记录收到的消息
+    struct Flaky {
+        failures: Mutex<usize>,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Messenger for Flaky {
+        fn send(&self, msg: &str) -> io::Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(io::Error::other("timed out"));
+            }
+            self.received.lock().unwrap().push(String::from(msg));
+            Ok(())
+        }
+    }
+
+    fn flaky(failures: usize, received: &Arc<Mutex<Vec<String>>>) -> Flaky {
+        Flaky {
+            failures: Mutex::new(failures),
+            received: received.clone(),
+        }
+    }
+
+    #[test]
+    fn forwards_to_every_backend() {
+        let first = Arc::new(Mutex::new(vec![]));
+        let second = Arc::new(Mutex::new(vec![]));
+        let messenger = FanOutMessenger::new()
+            .add("first", Recorder(first.clone()))
+            .add("broken", Broken)
+            .add("second", Recorder(second.clone()));
+
+        let failures = messenger.broadcast(Severity::Warning, "over 75%");
+
+        assert_eq!(vec!["WARNING over 75%"], *first.lock().unwrap());
+        assert_eq!(vec!["WARNING over 75%"], *second.lock().unwrap());
+        assert_eq!(1, failures.len());
+        assert_eq!("broken", failures[0].backend);
+        assert_eq!("connection refused", failures[0].error.to_string());
+    }
+
+    #[test]
+    fn deliver_reports_all_failures() {
+        let messenger = FanOutMessenger::new().add("a", Broken).add("b", Broken);
+
+        let error = messenger
+            .send_alert(Severity::Error, "over quota")
+            .unwrap_err();
+
+        assert_eq!(
+            "a: connection refused; b: connection refused",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn redeliver_skips_backends_that_succeeded() {
+        let working = Arc::new(Mutex::new(vec![]));
+        let failing = Arc::new(Mutex::new(vec![]));
+        let messenger = FanOutMessenger::new()
+            .add("working", Recorder(working.clone()))
+            .add("failing", flaky(1, &failing));
+        let mut tracker = LimitTracker::new(&messenger, 100);
+
+        tracker.set_value(80);
+        assert_eq!(1, tracker.dead_letters().len());
+        assert_eq!(
+            "failing: timed out",
+            tracker
+                .dead_letters()
+                .iter()
+                .next()
+                .unwrap()
+                .error
+                .to_string()
+        );
+
+        assert_eq!(1, tracker.redeliver());
+
+        let warning = "Warning: You've used up over 75% of your quota!";
+        assert_eq!(
+            vec![format!("WARNING {}", warning)],
+            *working.lock().unwrap()
+        );
+        assert_eq!(vec![warning], *failing.lock().unwrap());
+    }
+
+    #[test]
+    fn retries_only_failed_backends() {
+        let working = Arc::new(Mutex::new(vec![]));
+        let failing = Arc::new(Mutex::new(vec![]));
+        let messenger = FanOutMessenger::new()
+            .add("working", Recorder(working.clone()))
+            .add("failing", flaky(2, &failing));
+        let mut tracker =
+            LimitTracker::new(&messenger, 100).with_retry(Retry::new(3, Duration::ZERO));
+
+        tracker.set_value(100);
+
+        assert!(tracker.dead_letters().is_empty());
+        assert_eq!(1, working.lock().unwrap().len());
+        assert_eq!(1, failing.lock().unwrap().len());
+    }
+}
@@ -0,0 +1,141 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{Messenger, Severity};
+
+/// 追加写入文件的后端, 文件超过 `max_bytes` 时轮转
+///
+/// 轮转时 `alerts.log` 改名为 `alerts.log.1`, 原来的 `alerts.log.1` 改名为 `alerts.log.2`,
+/// 以此类推, 最多保留 `keep` 个旧文件.
+pub struct FileMessenger {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    // 保证同一时间只有一个线程在写入或轮转
+    lock: Mutex<()>,
+}
+
+impl FileMessenger {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, keep: usize) -> FileMessenger {
+        FileMessenger {
+            path: path.into(),
+            max_bytes,
+            keep,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 第 `n` 个旧文件的路径
+    pub fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+
+        for n in (1..self.keep).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(n + 1))?;
+            }
+        }
+
+        fs::rename(&self.path, self.rotated_path(1))
+    }
+}
+
+impl Messenger for FileMessenger {
+    fn send(&self, msg: &str) -> io::Result<()> {
+        self.send_alert(Severity::Info, msg)
+    }
+
+    fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let line = format!("[{}] {}\n", severity, msg);
+        let len = fs::metadata(&self.path).map_or(0, |m| m.len());
+
+        if len > 0 && len + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("testdobule-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn appends_lines() {
+        let dir = temp_dir("append");
+        let messenger = FileMessenger::new(dir.join("alerts.log"), 1024, 2);
+
+        messenger.send_alert(Severity::Warning, "first").unwrap();
+        messenger.send_alert(Severity::Error, "second").unwrap();
+
+        assert_eq!(
+            "[WARNING] first\n[ERROR] second\n",
+            fs::read_to_string(messenger.path()).unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_and_keeps_old_files() {
+        let dir = temp_dir("rotate");
+        // 每行 "[INFO] n\n" 9 个字节, 每个文件只放得下两行
+        let messenger = FileMessenger::new(dir.join("alerts.log"), 20, 2);
+
+        for n in 1..=7 {
+            messenger
+                .send_alert(Severity::Info, &n.to_string())
+                .unwrap();
+        }
+
+        assert_eq!("[INFO] 7\n", fs::read_to_string(messenger.path()).unwrap());
+        assert_eq!(
+            "[INFO] 5\n[INFO] 6\n",
+            fs::read_to_string(messenger.rotated_path(1)).unwrap()
+        );
+        assert_eq!(
+            "[INFO] 3\n[INFO] 4\n",
+            fs::read_to_string(messenger.rotated_path(2)).unwrap()
+        );
+        assert!(!messenger.rotated_path(3).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_directory_is_an_error() {
+        let dir = temp_dir("missing");
+        let messenger = FileMessenger::new(dir.join("no-such-dir/alerts.log"), 20, 2);
+
+        assert!(messenger.send_alert(Severity::Info, "lost").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::{Messenger, Severity};
+
+/// 把消息以 JSON 形式 POST 到一个 HTTP 地址, 可以作为 webhook 的替身
+///
+/// 只支持 `http://`, 每条消息使用一个新连接.
+pub struct HttpMessenger {
+    host: String,
+    path: String,
+    timeout: Duration,
+}
+
+impl HttpMessenger {
+    /// `url` 形如 `http://127.0.0.1:8080/alerts`
+    pub fn new(url: &str) -> Result<HttpMessenger, &'static str> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or("only http:// urls are supported")?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+
+        if host.is_empty() {
+            return Err("missing host");
+        }
+
+        let host = if host.contains(':') {
+            String::from(host)
+        } else {
+            format!("{}:80", host)
+        };
+
+        Ok(HttpMessenger {
+            host,
+            path: String::from(path),
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// 建立连接以及每次读写的超时时间, 默认 5 秒
+    pub fn with_timeout(mut self, timeout: Duration) -> HttpMessenger {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 依次尝试解析出的每个地址, 每个地址最多等待 `timeout`
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last_error = None;
+        for addr in self.host.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} did not resolve to any address", self.host),
+            )
+        }))
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Messenger for HttpMessenger {
+    fn send(&self, msg: &str) -> io::Result<()> {
+        self.send_alert(Severity::Info, msg)
+    }
+
+    fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+        let body = format!(
+            "{{\"severity\":\"{}\",\"message\":\"{}\"}}",
+            severity,
+            escape_json(msg)
+        );
+
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+
+        // 只关心状态行, 例如 "HTTP/1.1 200 OK"
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line)?;
+
+        let status = status_line.split_whitespace().nth(1).unwrap_or("");
+        if status.starts_with('2') {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "unexpected response: {}",
+                status_line.trim_end()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// 在本地端口上接收一个请求, 用 `status` 应答, 返回收到的请求
+    fn serve_once(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/alerts", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.strip_prefix("Content-Length: ") {
+                    content_length = len.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n",
+                status
+            )
+            .unwrap();
+
+            head + &String::from_utf8(body).unwrap()
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn posts_json() {
+        let (url, server) = serve_once("200 OK");
+        let messenger = HttpMessenger::new(&url).unwrap();
+
+        messenger
+            .send_alert(Severity::Error, "Error: \"quota\" exceeded")
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /alerts HTTP/1.1\r\n"));
+        assert!(request.ends_with(
+            "\r\n\r\n{\"severity\":\"ERROR\",\"message\":\"Error: \\\"quota\\\" exceeded\"}"
+        ));
+    }
+
+    #[test]
+    fn error_status_is_a_failure() {
+        let (url, server) = serve_once("500 Internal Server Error");
+        let messenger = HttpMessenger::new(&url).unwrap();
+
+        let error = messenger.send_alert(Severity::Warning, "hi").unwrap_err();
+
+        server.join().unwrap();
+        assert_eq!(
+            "unexpected response: HTTP/1.1 500 Internal Server Error",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn refused_connection_is_a_failure() {
+        // 绑定后立即关闭, 得到一个没有监听的端口
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let messenger = HttpMessenger::new(&format!("http://{}/alerts", addr))
+            .unwrap()
+            .with_timeout(Duration::from_millis(200));
+
+        assert!(messenger.send_alert(Severity::Warning, "hi").is_err());
+    }
+
+    #[test]
+    fn parses_urls() {
+        assert!(HttpMessenger::new("https://example.com").is_err());
+        assert!(HttpMessenger::new("http:///alerts").is_err());
+
+        let messenger = HttpMessenger::new("http://example.com").unwrap();
+        assert_eq!("example.com:80", messenger.host);
+        assert_eq!("/", messenger.path);
+    }
+
+    #[test]
+    fn escapes_json() {
+        assert_eq!("a\\\"b\\\\c\\nd\\u0001", escape_json("a\"b\\c\nd\u{1}"));
+    }
+}
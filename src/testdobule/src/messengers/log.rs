@@ -0,0 +1,73 @@
+use std::io::{self, Stderr, Write};
+use std::sync::Mutex;
+
+use crate::{Messenger, Severity};
+
+/// 把每条消息写成一行日志: `[WARNING] Warning: ...`
+pub struct LogMessenger<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl LogMessenger<Stderr> {
+    pub fn stderr() -> LogMessenger<Stderr> {
+        LogMessenger::new(io::stderr())
+    }
+}
+
+impl<W: Write> LogMessenger<W> {
+    pub fn new(writer: W) -> LogMessenger<W> {
+        LogMessenger {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<W: Write> Messenger for LogMessenger<W> {
+    fn send(&self, msg: &str) -> io::Result<()> {
+        self.send_alert(Severity::Info, msg)
+    }
+
+    fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(writer, "[{}] {}", severity, msg)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LimitTracker;
+
+    #[test]
+    fn writes_log_lines() {
+        let messenger = LogMessenger::new(Vec::new());
+
+        messenger
+            .send_alert(Severity::Urgent, "Urgent warning: over 90%")
+            .unwrap();
+        messenger.send("all good").unwrap();
+
+        assert_eq!(
+            "[URGENT] Urgent warning: over 90%\n[INFO] all good\n",
+            String::from_utf8(messenger.into_inner()).unwrap()
+        );
+    }
+
+    #[test]
+    fn works_with_limit_tracker() {
+        let messenger = LogMessenger::new(Vec::new());
+        let mut limit_tracker = LimitTracker::new(&messenger, 100);
+
+        limit_tracker.set_value(80);
+
+        assert_eq!(
+            "[WARNING] Warning: You've used up over 75% of your quota!\n",
+            String::from_utf8(messenger.into_inner()).unwrap()
+        );
+    }
+}
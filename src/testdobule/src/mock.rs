@@ -0,0 +1,529 @@
+//! 测试替身工具: 记录调用、匹配参数、校验调用次数和顺序, 以及预设返回值
+//!
+//! ```
+//! use testdobule::mock::{alert, any, call, contains, eq, MockMessenger};
+//! use testdobule::{LimitTracker, Severity};
+//!
+//! let messenger = MockMessenger::new();
+//! messenger
+//!     .calls
+//!     .expect(call(alert(eq(Severity::Warning), contains("75%"))).times(1));
+//!
+//! let mut tracker = LimitTracker::new(&messenger, 100);
+//! tracker.set_value(80);
+//!
+//! messenger.calls.verify();
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{Messenger, Severity};
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // 断言失败时的 panic 不应该让后续的检查也跟着失败
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 参数匹配器, 带有用于失败信息的描述
+pub struct Matcher<A> {
+    description: String,
+    predicate: Box<dyn Fn(&A) -> bool + Send + Sync>,
+}
+
+impl<A> Matcher<A> {
+    pub fn new(
+        description: &str,
+        predicate: impl Fn(&A) -> bool + Send + Sync + 'static,
+    ) -> Matcher<A> {
+        Matcher {
+            description: String::from(description),
+            predicate: Box::new(predicate),
+        }
+    }
+
+    pub fn matches(&self, arg: &A) -> bool {
+        (self.predicate)(arg)
+    }
+}
+
+impl<A> fmt::Display for Matcher<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.description)
+    }
+}
+
+pub fn any<A>() -> Matcher<A> {
+    Matcher::new("any", |_| true)
+}
+
+pub fn eq<A>(expected: A) -> Matcher<A>
+where
+    A: PartialEq + Debug + Send + Sync + 'static,
+{
+    Matcher::new(&format!("{:?}", expected), move |arg| *arg == expected)
+}
+
+pub fn contains(needle: &str) -> Matcher<String> {
+    let needle = String::from(needle);
+    Matcher::new(&format!("contains {:?}", needle), move |arg: &String| {
+        arg.contains(&needle)
+    })
+}
+
+pub fn not<A: 'static>(matcher: Matcher<A>) -> Matcher<A> {
+    Matcher::new(&format!("not {}", matcher), move |arg| {
+        !matcher.matches(arg)
+    })
+}
+
+/// 匹配 `Messenger` 收到的 `(严重程度, 消息)`
+pub fn alert(severity: Matcher<Severity>, message: Matcher<String>) -> Matcher<(Severity, String)> {
+    Matcher::new(
+        &format!("alert({}, {})", severity, message),
+        move |(s, m): &(Severity, String)| severity.matches(s) && message.matches(m),
+    )
+}
+
+/// 期望的调用次数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Times {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+}
+
+impl Times {
+    pub fn allows(&self, count: usize) -> bool {
+        match *self {
+            Times::Exactly(n) => count == n,
+            Times::AtLeast(n) => count >= n,
+            Times::AtMost(n) => count <= n,
+        }
+    }
+}
+
+impl fmt::Display for Times {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Times::Exactly(n) => write!(f, "exactly {}", n),
+            Times::AtLeast(n) => write!(f, "at least {}", n),
+            Times::AtMost(n) => write!(f, "at most {}", n),
+        }
+    }
+}
+
+pub struct Expectation<A> {
+    matcher: Matcher<A>,
+    times: Times,
+    ordered: bool,
+}
+
+/// 期望一次匹配 `matcher` 的调用, 默认恰好一次
+pub fn call<A>(matcher: Matcher<A>) -> Expectation<A> {
+    Expectation {
+        matcher,
+        times: Times::Exactly(1),
+        ordered: false,
+    }
+}
+
+impl<A> Expectation<A> {
+    pub fn times(mut self, n: usize) -> Expectation<A> {
+        self.times = Times::Exactly(n);
+        self
+    }
+
+    pub fn at_least(mut self, n: usize) -> Expectation<A> {
+        self.times = Times::AtLeast(n);
+        self
+    }
+
+    pub fn at_most(mut self, n: usize) -> Expectation<A> {
+        self.times = Times::AtMost(n);
+        self
+    }
+
+    pub fn never(self) -> Expectation<A> {
+        self.times(0)
+    }
+
+    /// 所有标记了 `in_order` 的期望, 其匹配的调用必须按照添加期望的顺序出现
+    pub fn in_order(mut self) -> Expectation<A> {
+        self.ordered = true;
+        self
+    }
+}
+
+/// 调用记录器, 同时保存期望并在 `verify` 时统一校验
+pub struct Mock<A> {
+    calls: Mutex<Vec<A>>,
+    expectations: Mutex<Vec<Expectation<A>>>,
+}
+
+impl<A: Debug> Mock<A> {
+    pub fn new() -> Mock<A> {
+        Mock {
+            calls: Mutex::new(vec![]),
+            expectations: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn record(&self, args: A) {
+        lock(&self.calls).push(args);
+    }
+
+    pub fn call_count(&self) -> usize {
+        lock(&self.calls).len()
+    }
+
+    /// 匹配 `matcher` 的调用次数
+    pub fn count(&self, matcher: &Matcher<A>) -> usize {
+        lock(&self.calls)
+            .iter()
+            .filter(|args| matcher.matches(args))
+            .count()
+    }
+
+    pub fn expect(&self, expectation: Expectation<A>) {
+        lock(&self.expectations).push(expectation);
+    }
+
+    /// 校验所有期望, 失败时返回描述所有问题和全部调用记录的信息
+    pub fn check(&self) -> Result<(), String> {
+        let calls = lock(&self.calls);
+        let expectations = lock(&self.expectations);
+        let mut problems = vec![];
+
+        for expectation in expectations.iter() {
+            let count = calls
+                .iter()
+                .filter(|args| expectation.matcher.matches(args))
+                .count();
+            if !expectation.times.allows(count) {
+                problems.push(format!(
+                    "expected {} call(s) matching {}, got {}",
+                    expectation.times, expectation.matcher, count
+                ));
+            }
+        }
+
+        let ordered: Vec<&Expectation<A>> = expectations.iter().filter(|e| e.ordered).collect();
+        let mut position = 0;
+        for (i, args) in calls.iter().enumerate() {
+            let matched = ordered[position..]
+                .iter()
+                .position(|e| e.matcher.matches(args))
+                .map(|offset| position + offset);
+
+            match matched {
+                Some(index) => position = index,
+                None => {
+                    if let Some(earlier) = ordered[..position]
+                        .iter()
+                        .position(|e| e.matcher.matches(args))
+                    {
+                        problems.push(format!(
+                            "call #{} {:?} matches {} but came after a call matching {}",
+                            i, args, ordered[earlier].matcher, ordered[position].matcher
+                        ));
+                    }
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        let mut report = String::from("unsatisfied expectations:\n");
+        for problem in problems {
+            report.push_str(&format!("  - {}\n", problem));
+        }
+        report.push_str("recorded calls:\n");
+        if calls.is_empty() {
+            report.push_str("  (none)\n");
+        }
+        for (i, args) in calls.iter().enumerate() {
+            report.push_str(&format!("  #{} {:?}\n", i, args));
+        }
+
+        Err(report)
+    }
+
+    /// 校验所有期望, 失败时 panic
+    pub fn verify(&self) {
+        if let Err(report) = self.check() {
+            panic!("{}", report);
+        }
+    }
+}
+
+impl<A: Clone> Mock<A> {
+    pub fn calls(&self) -> Vec<A> {
+        lock(&self.calls).clone()
+    }
+}
+
+impl<A: Debug> Default for Mock<A> {
+    fn default() -> Mock<A> {
+        Mock::new()
+    }
+}
+
+/// 预设返回值: 按顺序返回 `then` 加入的值, 用完后返回 `fallback` 的结果
+pub struct Stub<R> {
+    queue: Mutex<VecDeque<R>>,
+    fallback: Box<dyn Fn() -> R + Send + Sync>,
+}
+
+impl<R> Stub<R> {
+    pub fn new(fallback: impl Fn() -> R + Send + Sync + 'static) -> Stub<R> {
+        Stub {
+            queue: Mutex::new(VecDeque::new()),
+            fallback: Box::new(fallback),
+        }
+    }
+
+    pub fn then(&self, value: R) -> &Stub<R> {
+        lock(&self.queue).push_back(value);
+        self
+    }
+
+    pub fn next(&self) -> R {
+        let queued = lock(&self.queue).pop_front();
+        queued.unwrap_or_else(|| (self.fallback)())
+    }
+}
+
+/// 记录收到的消息, 返回值由 `results` 决定(默认成功)
+pub struct MockMessenger {
+    pub calls: Mock<(Severity, String)>,
+    pub results: Stub<io::Result<()>>,
+}
+
+impl MockMessenger {
+    pub fn new() -> MockMessenger {
+        MockMessenger {
+            calls: Mock::new(),
+            results: Stub::new(|| Ok(())),
+        }
+    }
+
+    /// 接下来的 `n` 次发送失败
+    pub fn fail_next(&self, n: usize, error: &str) {
+        for _ in 0..n {
+            self.results.then(Err(io::Error::other(error.to_string())));
+        }
+    }
+
+    pub fn messages(&self) -> Vec<String> {
+        self.calls.calls().into_iter().map(|(_, m)| m).collect()
+    }
+
+    pub fn severities(&self) -> Vec<Severity> {
+        self.calls.calls().into_iter().map(|(s, _)| s).collect()
+    }
+}
+
+impl Default for MockMessenger {
+    fn default() -> MockMessenger {
+        MockMessenger::new()
+    }
+}
+
+impl Messenger for MockMessenger {
+    fn send(&self, msg: &str) -> io::Result<()> {
+        self.send_alert(Severity::Info, msg)
+    }
+
+    fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+        self.calls.record((severity, String::from(msg)));
+        self.results.next()
+    }
+}
+
+/// 包装一个真实的 `Messenger`, 转发每条消息并记录下来
+pub struct Spy<M: Messenger> {
+    inner: M,
+    pub calls: Mock<(Severity, String)>,
+}
+
+impl<M: Messenger> Spy<M> {
+    pub fn new(inner: M) -> Spy<M> {
+        Spy {
+            inner,
+            calls: Mock::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M: Messenger> Messenger for Spy<M> {
+    fn send(&self, msg: &str) -> io::Result<()> {
+        self.send_alert(Severity::Info, msg)
+    }
+
+    fn send_alert(&self, severity: Severity, msg: &str) -> io::Result<()> {
+        self.calls.record((severity, String::from(msg)));
+        self.inner.send_alert(severity, msg)
+    }
+
+    fn resend_alert(&self, severity: Severity, msg: &str, error: &io::Error) -> io::Result<()> {
+        self.calls.record((severity, String::from(msg)));
+        self.inner.resend_alert(severity, msg, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messengers::LogMessenger;
+
+    #[test]
+    fn matchers() {
+        assert!(any::<i32>().matches(&7));
+        assert!(eq(3).matches(&3));
+        assert!(!eq(3).matches(&4));
+        assert!(contains("quota").matches(&String::from("over quota")));
+        assert!(not(eq(3)).matches(&4));
+
+        let matcher = alert(eq(Severity::Error), contains("over"));
+        assert_eq!("alert(Error, contains \"over\")", matcher.to_string());
+        assert!(matcher.matches(&(Severity::Error, String::from("over quota"))));
+        assert!(!matcher.matches(&(Severity::Warning, String::from("over quota"))));
+    }
+
+    #[test]
+    fn call_counts() {
+        let mock = Mock::new();
+        mock.expect(call(eq(1)).times(2));
+        mock.expect(call(eq(2)).at_least(1));
+        mock.expect(call(eq(3)).never());
+        mock.expect(call(any()).at_most(3));
+
+        mock.record(1);
+        mock.record(2);
+        mock.record(1);
+
+        assert_eq!(3, mock.call_count());
+        assert_eq!(2, mock.count(&eq(1)));
+        assert_eq!(Ok(()), mock.check());
+    }
+
+    #[test]
+    fn count_failure_message() {
+        let mock = Mock::new();
+        mock.expect(call(eq("a")).times(1));
+        mock.expect(call(eq("b")).never());
+
+        mock.record("b");
+
+        assert_eq!(
+            Err(String::from(
+                "\
+unsatisfied expectations:
+  - expected exactly 1 call(s) matching \"a\", got 0
+  - expected exactly 0 call(s) matching \"b\", got 1
+recorded calls:
+  #0 \"b\"
+"
+            )),
+            mock.check()
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let mock = Mock::new();
+        mock.expect(call(eq(1)).in_order());
+        mock.expect(call(eq(2)).at_least(1).in_order());
+        mock.expect(call(eq(3)).in_order());
+
+        for n in [1, 9, 2, 2, 3] {
+            mock.record(n);
+        }
+        assert_eq!(Ok(()), mock.check());
+
+        mock.record(1);
+        assert_eq!(
+            Err(String::from(
+                "\
+unsatisfied expectations:
+  - expected exactly 1 call(s) matching 1, got 2
+  - call #5 1 matches 1 but came after a call matching 3
+recorded calls:
+  #0 1
+  #1 9
+  #2 2
+  #3 2
+  #4 3
+  #5 1
+"
+            )),
+            mock.check()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected exactly 1 call(s) matching any, got 0")]
+    fn verify_panics() {
+        let mock: Mock<i32> = Mock::new();
+        mock.expect(call(any()));
+
+        mock.verify();
+    }
+
+    #[test]
+    fn stub_returns_queued_values_then_fallback() {
+        let stub = Stub::new(|| 0);
+        stub.then(1).then(2);
+
+        assert_eq!(
+            vec![1, 2, 0, 0],
+            (0..4).map(|_| stub.next()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mock_messenger_fails_on_demand() {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(1, "down");
+
+        assert_eq!(
+            "down",
+            messenger
+                .send_alert(Severity::Error, "a")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(messenger.send("b").is_ok());
+        assert_eq!(vec!["a", "b"], messenger.messages());
+        assert_eq!(
+            vec![Severity::Error, Severity::Info],
+            messenger.severities()
+        );
+    }
+
+    #[test]
+    fn spy_forwards_and_records() {
+        let spy = Spy::new(LogMessenger::new(Vec::new()));
+
+        spy.send_alert(Severity::Warning, "careful").unwrap();
+
+        spy.calls.expect(call(alert(
+            eq(Severity::Warning),
+            eq(String::from("careful")),
+        )));
+        spy.calls.verify();
+        assert_eq!(
+            "[WARNING] careful\n",
+            String::from_utf8(spy.into_inner().into_inner()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt;
+
+/// 告警的严重程度, 供告警系统区分路由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// 用量恢复正常等提示信息
+    Info,
+    Warning,
+    Urgent,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Urgent => "URGENT",
+            Severity::Error => "ERROR",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 一档阈值: 用量达到 `percent`% 时, 以 `severity` 发送 `message`
+///
+/// `message` 是模板, 其中的 `{value}`、`{max}`、`{percent}` 会被替换为当前值、上限和该档的百分比.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threshold {
+    pub percent: u32,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Threshold {
+    pub fn new(percent: u32, severity: Severity, message: &str) -> Threshold {
+        Threshold {
+            percent,
+            severity,
+            message: String::from(message),
+        }
+    }
+
+    pub fn is_reached(&self, value: usize, max: usize) -> bool {
+        self.is_held(value, max, 0)
+    }
+
+    /// 已处于该档时, 用量不低于 `percent - band`% 就仍算作该档
+    ///
+    /// `max` 为 0 表示没有配额, 不处于任何一档.
+    pub fn is_held(&self, value: usize, max: usize, band: u32) -> bool {
+        if max == 0 {
+            return false;
+        }
+        let percent = self.percent.saturating_sub(band);
+        // 用整数比较, 避免浮点误差
+        value as u128 * 100 >= max as u128 * percent as u128
+    }
+
+    pub fn render(&self, value: usize, max: usize) -> String {
+        self.message
+            .replace("{value}", &value.to_string())
+            .replace("{max}", &max.to_string())
+            .replace("{percent}", &self.percent.to_string())
+    }
+}
+
+/// 按百分比从低到高排列的一组阈值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    thresholds: Vec<Threshold>,
+    hysteresis: u32,
+    recovery_message: String,
+}
+
+impl Policy {
+    pub fn new(mut thresholds: Vec<Threshold>) -> Policy {
+        thresholds.sort_by_key(|threshold| threshold.percent);
+        Policy {
+            thresholds,
+            hysteresis: 0,
+            recovery_message: String::from(
+                "Recovered: You are back below {percent}% of your quota.",
+            ),
+        }
+    }
+
+    /// 回差(百分点): 用量要比当前档低出这么多才会降档, 避免在阈值附近来回告警
+    pub fn with_hysteresis(mut self, band: u32) -> Policy {
+        self.hysteresis = band;
+        self
+    }
+
+    /// 用量降到最低一档以下时发送的消息模板, `{percent}` 为最低一档的百分比
+    pub fn with_recovery_message(mut self, message: &str) -> Policy {
+        self.recovery_message = String::from(message);
+        self
+    }
+
+    pub fn thresholds(&self) -> &[Threshold] {
+        &self.thresholds
+    }
+
+    pub fn hysteresis(&self) -> u32 {
+        self.hysteresis
+    }
+
+    /// 返回已达到的最高一档
+    pub fn tier(&self, value: usize, max: usize) -> Option<&Threshold> {
+        self.tier_index(value, max).map(|i| &self.thresholds[i])
+    }
+
+    pub fn tier_index(&self, value: usize, max: usize) -> Option<usize> {
+        self.thresholds
+            .iter()
+            .rposition(|threshold| threshold.is_reached(value, max))
+    }
+
+    /// 当前处于 `current` 档时, 根据新的用量计算应处于哪一档
+    ///
+    /// 升档立即生效, 降档需要低于回差范围.
+    pub fn next_tier(&self, current: Option<usize>, value: usize, max: usize) -> Option<usize> {
+        let reached = self.tier_index(value, max);
+
+        match current {
+            Some(current) if reached < Some(current) => self.thresholds[..=current]
+                .iter()
+                .rposition(|threshold| threshold.is_held(value, max, self.hysteresis)),
+            _ => reached,
+        }
+    }
+
+    /// 更新 `alerted` 记录的档位, 档位发生变化时返回需要发送的那一档
+    pub fn transition(
+        &self,
+        alerted: &mut Option<usize>,
+        value: usize,
+        max: usize,
+    ) -> Option<Threshold> {
+        let tier = self.next_tier(*alerted, value, max);
+        if tier == *alerted {
+            return None;
+        }
+        *alerted = tier;
+
+        Some(self.alert(tier))
+    }
+
+    /// 进入 `tier` 档时要发送的消息, `None` 为恢复消息
+    pub fn alert(&self, tier: Option<usize>) -> Threshold {
+        match tier {
+            Some(i) => self.thresholds[i].clone(),
+            None => self.recovery(),
+        }
+    }
+
+    /// 恢复消息, 以最低一档的百分比渲染
+    pub fn recovery(&self) -> Threshold {
+        let percent = self.thresholds.first().map_or(0, |t| t.percent);
+        Threshold::new(percent, Severity::Info, &self.recovery_message)
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy::new(vec![
+            Threshold::new(
+                75,
+                Severity::Warning,
+                "Warning: You've used up over 75% of your quota!",
+            ),
+            Threshold::new(
+                90,
+                Severity::Urgent,
+                "Urgent warning: You've used up over 90% of your quota!",
+            ),
+            Threshold::new(100, Severity::Error, "Error: You are over your quota!"),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_are_sorted() {
+        let policy = Policy::new(vec![
+            Threshold::new(90, Severity::Error, "b"),
+            Threshold::new(50, Severity::Warning, "a"),
+        ]);
+
+        let percents: Vec<u32> = policy.thresholds().iter().map(|t| t.percent).collect();
+        assert_eq!(vec![50, 90], percents);
+    }
+
+    #[test]
+    fn highest_reached_tier() {
+        let policy = Policy::default();
+
+        assert_eq!(None, policy.tier(74, 100));
+        assert_eq!(Severity::Warning, policy.tier(75, 100).unwrap().severity);
+        assert_eq!(Severity::Urgent, policy.tier(99, 100).unwrap().severity);
+        assert_eq!(Severity::Error, policy.tier(150, 100).unwrap().severity);
+    }
+
+    #[test]
+    fn exact_percentages() {
+        // 29 / 100 用浮点数计算会略小于 0.29
+        assert!(Threshold::new(29, Severity::Warning, "").is_reached(29, 100));
+        assert!(!Threshold::new(29, Severity::Warning, "").is_reached(28, 100));
+    }
+
+    #[test]
+    fn zero_max_means_no_quota() {
+        let policy = Policy::default();
+
+        assert_eq!(None, policy.tier(0, 0));
+        assert_eq!(None, policy.tier(10, 0));
+        assert_eq!(None, policy.transition(&mut None, 0, 0));
+    }
+
+    #[test]
+    fn hysteresis_delays_downgrade() {
+        let policy = Policy::default().with_hysteresis(5);
+
+        // 处于 90% 档时, 降到 85% 仍保持, 降到 84% 才回到 75% 档
+        assert_eq!(Some(1), policy.next_tier(Some(1), 85, 100));
+        assert_eq!(Some(0), policy.next_tier(Some(1), 84, 100));
+        // 升档不受回差影响
+        assert_eq!(Some(2), policy.next_tier(Some(0), 100, 100));
+        assert_eq!(Some(0), policy.next_tier(Some(0), 71, 100));
+        assert_eq!(None, policy.next_tier(Some(2), 69, 100));
+        assert_eq!(None, policy.next_tier(None, 74, 100));
+    }
+
+    #[test]
+    fn message_template() {
+        let threshold = Threshold::new(80, Severity::Warning, "{value}/{max} is over {percent}%");
+
+        assert_eq!("85/100 is over 80%", threshold.render(85, 100));
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::delivery::{self, DeadLetters, Retry};
+use crate::{Messenger, Policy};
+
+struct Quota {
+    value: usize,
+    max: usize,
+    alerted: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownQuota {
+    pub name: String,
+}
+
+impl fmt::Display for UnknownQuota {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown quota: {}", self.name)
+    }
+}
+
+impl Error for UnknownQuota {}
+
+/// 用同一个 `Messenger` 跟踪多个命名配额, 例如每个用户或每种资源一个
+///
+/// 所有配额共用一个 `Policy`, 发出的消息前会加上配额名和当前用量.
+pub struct QuotaRegistry<'a, T: Messenger> {
+    messenger: &'a T,
+    policy: Policy,
+    quotas: HashMap<String, Quota>,
+    retry: Retry,
+    dead_letters: DeadLetters,
+}
+
+impl<'a, T> QuotaRegistry<'a, T>
+where
+    T: Messenger,
+{
+    pub fn new(messenger: &'a T) -> QuotaRegistry<'a, T> {
+        QuotaRegistry::with_policy(messenger, Policy::default())
+    }
+
+    pub fn with_policy(messenger: &'a T, policy: Policy) -> QuotaRegistry<'a, T> {
+        QuotaRegistry {
+            messenger,
+            policy,
+            quotas: HashMap::new(),
+            retry: Retry::default(),
+            dead_letters: DeadLetters::default(),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> QuotaRegistry<'a, T> {
+        self.retry = retry;
+        self
+    }
+
+    /// 重试后仍未发送出去的消息
+    pub fn dead_letters(&self) -> &DeadLetters {
+        &self.dead_letters
+    }
+
+    /// 重新发送死信, 返回成功发送的数量
+    pub fn redeliver(&mut self) -> usize {
+        self.dead_letters.redeliver(self.messenger, self.retry)
+    }
+
+    /// 添加配额, 同名配额会被重置
+    pub fn add_quota(&mut self, name: &str, max: usize) {
+        self.quotas.insert(
+            String::from(name),
+            Quota {
+                value: 0,
+                max,
+                alerted: None,
+            },
+        );
+    }
+
+    pub fn remove_quota(&mut self, name: &str) -> bool {
+        self.quotas.remove(name).is_some()
+    }
+
+    pub fn value(&self, name: &str) -> Option<usize> {
+        self.quotas.get(name).map(|quota| quota.value)
+    }
+
+    pub fn set_value(&mut self, name: &str, value: usize) -> Result<(), UnknownQuota> {
+        let quota = self.quotas.get_mut(name).ok_or_else(|| UnknownQuota {
+            name: String::from(name),
+        })?;
+        quota.value = value;
+
+        let transition = self
+            .policy
+            .transition(&mut quota.alerted, quota.value, quota.max);
+
+        if let Some(threshold) = transition {
+            let message = format!(
+                "{} ({}/{}): {}",
+                name,
+                quota.value,
+                quota.max,
+                threshold.render(quota.value, quota.max)
+            );
+            if let Err(letter) =
+                delivery::deliver(self.messenger, self.retry, threshold.severity, &message)
+            {
+                self.dead_letters.push(letter);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 批量更新; 只要有一个配额不存在就不做任何更新
+    pub fn set_values<I, S>(&mut self, values: I) -> Result<(), UnknownQuota>
+    where
+        I: IntoIterator<Item = (S, usize)>,
+        S: AsRef<str>,
+    {
+        let values: Vec<(S, usize)> = values.into_iter().collect();
+
+        if let Some((name, _)) = values
+            .iter()
+            .find(|(name, _)| !self.quotas.contains_key(name.as_ref()))
+        {
+            return Err(UnknownQuota {
+                name: String::from(name.as_ref()),
+            });
+        }
+
+        for (name, value) in values {
+            self.set_value(name.as_ref(), value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockMessenger;
+
+    #[test]
+    fn quotas_are_tracked_separately() {
+        let mock_messenger = MockMessenger::new();
+        let mut registry = QuotaRegistry::new(&mock_messenger);
+        registry.add_quota("alice", 100);
+        registry.add_quota("bob", 10);
+
+        registry.set_value("alice", 80).unwrap();
+        registry.set_value("bob", 8).unwrap();
+        registry.set_value("alice", 81).unwrap();
+
+        assert_eq!(Some(81), registry.value("alice"));
+        assert_eq!(
+            vec![
+                "alice (80/100): Warning: You've used up over 75% of your quota!",
+                "bob (8/10): Warning: You've used up over 75% of your quota!",
+            ],
+            mock_messenger.messages()
+        );
+    }
+
+    #[test]
+    fn bulk_update() {
+        let mock_messenger = MockMessenger::new();
+        let mut registry = QuotaRegistry::new(&mock_messenger);
+        registry.add_quota("cpu", 4);
+        registry.add_quota("disk", 1000);
+
+        registry
+            .set_values(vec![("cpu", 4), ("disk", 100)])
+            .unwrap();
+
+        assert_eq!(
+            vec!["cpu (4/4): Error: You are over your quota!"],
+            mock_messenger.messages()
+        );
+    }
+
+    #[test]
+    fn unknown_quota_updates_nothing() {
+        let mock_messenger = MockMessenger::new();
+        let mut registry = QuotaRegistry::new(&mock_messenger);
+        registry.add_quota("cpu", 4);
+
+        let result = registry.set_values([(String::from("cpu"), 4), (String::from("gpu"), 1)]);
+
+        assert_eq!(
+            Err(UnknownQuota {
+                name: String::from("gpu")
+            }),
+            result
+        );
+        assert_eq!(Some(0), registry.value("cpu"));
+        assert!(mock_messenger.messages().is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::delivery::{self, DeadLetters, Retry};
+use crate::{Messenger, Policy};
+
+/// 可以在多个线程间共享的 `LimitTracker`
+///
+/// 用量和告警档位都保存在原子变量中, `set_value`/`add` 只需要 `&self`.
+/// 档位变化通过 compare-and-swap 完成, 每次变化只会有一个线程发送消息.
+///
+/// 每次变化带有递增的版本号, 告警按版本号的顺序发送, 不会丢失也不会重复.
+/// 完成 CAS 的线程把变化放入队列; 此时没有其他线程在发送的话, 由它依次发送队列中
+/// 已经连续的变化, 否则直接返回, 由正在发送的线程接着发送. 发送时不持有任何锁,
+/// 一个很慢的后端只会拖慢正在发送的那个线程.
+/// 因此所有告警发送完之后, 最后收到的告警总是与保存的档位一致.
+pub struct SyncLimitTracker {
+    messenger: Arc<dyn Messenger + Send + Sync>,
+    value: AtomicUsize,
+    max: usize,
+    policy: Policy,
+    // 高 32 位为版本号, 低 32 位为档位加 1(0 表示未告警); 版本号用来避免 ABA 问题
+    state: AtomicU64,
+    retry: Retry,
+    dead_letters: Mutex<DeadLetters>,
+    pending: Mutex<Pending>,
+}
+
+/// 等待发送的档位变化
+struct Pending {
+    /// 下一个要发送的版本号
+    next: u32,
+    /// 版本号 -> (档位, 当时的用量)
+    changes: HashMap<u32, (Option<usize>, usize)>,
+    /// 是否有线程正在发送
+    delivering: bool,
+}
+
+fn encode(version: u32, tier: Option<usize>) -> u64 {
+    let tier = tier.map_or(0, |i| i as u64 + 1);
+    (version as u64) << 32 | tier
+}
+
+fn decode(state: u64) -> (u32, Option<usize>) {
+    let version = (state >> 32) as u32;
+    let tier = match state as u32 {
+        0 => None,
+        n => Some(n as usize - 1),
+    };
+    (version, tier)
+}
+
+impl SyncLimitTracker {
+    pub fn new(messenger: Arc<dyn Messenger + Send + Sync>, max: usize) -> SyncLimitTracker {
+        SyncLimitTracker::with_policy(messenger, max, Policy::default())
+    }
+
+    pub fn with_policy(
+        messenger: Arc<dyn Messenger + Send + Sync>,
+        max: usize,
+        policy: Policy,
+    ) -> SyncLimitTracker {
+        SyncLimitTracker {
+            messenger,
+            value: AtomicUsize::new(0),
+            max,
+            policy,
+            state: AtomicU64::new(encode(0, None)),
+            retry: Retry::default(),
+            dead_letters: Mutex::new(DeadLetters::default()),
+            pending: Mutex::new(Pending {
+                next: 1,
+                changes: HashMap::new(),
+                delivering: false,
+            }),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> SyncLimitTracker {
+        self.retry = retry;
+        self
+    }
+
+    /// 重试后仍未发送出去的消息
+    pub fn dead_letters(&self) -> MutexGuard<'_, DeadLetters> {
+        self.dead_letters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 重新发送死信, 返回成功发送的数量
+    pub fn redeliver(&self) -> usize {
+        // 先取出再发送, 重试等待时不持有锁
+        let mut pending = DeadLetters::default();
+        for letter in self.dead_letters().take() {
+            pending.push(letter);
+        }
+
+        let delivered = pending.redeliver(&*self.messenger, self.retry);
+
+        let mut dead_letters = self.dead_letters();
+        for letter in pending.take() {
+            dead_letters.push(letter);
+        }
+
+        delivered
+    }
+
+    pub fn value(&self) -> usize {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    pub fn set_value(&self, value: usize) {
+        self.value.store(value, Ordering::SeqCst);
+        self.check();
+    }
+
+    /// 用量增加 `delta`, 返回增加后的用量
+    pub fn add(&self, delta: usize) -> usize {
+        let value = self.value.fetch_add(delta, Ordering::SeqCst) + delta;
+        self.check();
+        value
+    }
+
+    fn check(&self) {
+        loop {
+            // 先读档位再读用量, 保证用量不会比决定当前档位的那次更旧
+            let state = self.state.load(Ordering::SeqCst);
+            let value = self.value.load(Ordering::SeqCst);
+            let (version, alerted) = decode(state);
+
+            let tier = self.policy.next_tier(alerted, value, self.max);
+            if tier == alerted {
+                return;
+            }
+
+            let version = version.wrapping_add(1);
+            if self
+                .state
+                .compare_exchange(
+                    state,
+                    encode(version, tier),
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_ok()
+            {
+                self.enqueue(version, tier, value);
+                return;
+            }
+            // 其他线程先改变了档位, 用最新的状态重试
+        }
+    }
+
+    fn pending(&self) -> MutexGuard<'_, Pending> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 把第 `version` 次档位变化放入队列, 没有其他线程在发送时按版本号顺序发送
+    fn enqueue(&self, version: u32, tier: Option<usize>, value: usize) {
+        let mut pending = self.pending();
+        pending.changes.insert(version, (tier, value));
+        if pending.delivering {
+            return;
+        }
+        pending.delivering = true;
+
+        loop {
+            let next = pending.next;
+            let (tier, value) = match pending.changes.remove(&next) {
+                Some(change) => change,
+                // 更早的变化还没有放入队列, 由放入它的线程接着发送
+                None => break,
+            };
+            pending.next = next.wrapping_add(1);
+            drop(pending);
+
+            self.send(tier, value);
+            pending = self.pending();
+        }
+        pending.delivering = false;
+    }
+
+    fn send(&self, tier: Option<usize>, value: usize) {
+        let threshold = self.policy.alert(tier);
+        let message = threshold.render(value, self.max);
+        if let Err(letter) =
+            delivery::deliver(&*self.messenger, self.retry, threshold.severity, &message)
+        {
+            self.dead_letters().push(letter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{alert, any, call, eq, MockMessenger};
+    use crate::Severity;
+    use std::thread;
+
+    /// 多个线程发送的顺序不确定, 排序后再比较
+    fn sorted_severities(messenger: &MockMessenger) -> Vec<Severity> {
+        let mut severities = messenger.severities();
+        severities.sort();
+        severities
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn tracker_is_send_and_sync() {
+        assert_send_sync::<SyncLimitTracker>();
+    }
+
+    #[test]
+    fn state_round_trip() {
+        assert_eq!((0, None), decode(encode(0, None)));
+        assert_eq!((7, Some(2)), decode(encode(7, Some(2))));
+        assert_eq!((u32::MAX, Some(0)), decode(encode(u32::MAX, Some(0))));
+    }
+
+    #[test]
+    fn it_sends_an_over_75_percent_warning_message() {
+        let messenger = Arc::new(MockMessenger::new());
+        let tracker = SyncLimitTracker::new(messenger.clone(), 100);
+
+        tracker.set_value(80);
+        tracker.set_value(80);
+
+        messenger
+            .calls
+            .expect(call(alert(eq(Severity::Warning), any())));
+        messenger.calls.verify();
+    }
+
+    #[test]
+    fn concurrent_adds_alert_each_tier_once() {
+        const THREADS: usize = 16;
+        const ADDS: usize = 1000;
+
+        for _ in 0..20 {
+            let messenger = Arc::new(MockMessenger::new());
+            let tracker = Arc::new(SyncLimitTracker::new(messenger.clone(), THREADS * ADDS));
+
+            let handles: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    let tracker = Arc::clone(&tracker);
+                    thread::spawn(move || {
+                        for _ in 0..ADDS {
+                            tracker.add(1);
+                        }
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                handle.join().unwrap();
+            }
+
+            assert_eq!(THREADS * ADDS, tracker.value());
+            assert_eq!(
+                vec![Severity::Warning, Severity::Urgent, Severity::Error],
+                sorted_severities(&messenger)
+            );
+        }
+    }
+
+    #[test]
+    fn racing_transitions_are_all_delivered_in_order() {
+        for _ in 0..200 {
+            let messenger = Arc::new(MockMessenger::new());
+            let tracker = Arc::new(SyncLimitTracker::new(messenger.clone(), 4));
+
+            // 用量 3 是 75% 档, 4 是 100% 档, 两次升档几乎同时发生
+            thread::scope(|scope| {
+                for _ in 0..4 {
+                    let tracker = &tracker;
+                    scope.spawn(move || tracker.add(1));
+                }
+            });
+
+            let (version, _) = decode(tracker.state.load(Ordering::SeqCst));
+            let severities = messenger.severities();
+            assert_eq!(version as usize, severities.len());
+            assert!(
+                severities == [Severity::Warning, Severity::Error]
+                    || severities == [Severity::Error]
+            );
+        }
+    }
+
+    #[test]
+    fn concurrent_set_values_settle_on_the_last_value() {
+        let messenger = Arc::new(MockMessenger::new());
+        let tracker = Arc::new(SyncLimitTracker::new(messenger.clone(), 100));
+
+        thread::scope(|scope| {
+            for offset in 0..8 {
+                let tracker = &tracker;
+                scope.spawn(move || {
+                    for value in (0..=110).cycle().skip(offset * 13).take(2000) {
+                        tracker.set_value(value);
+                    }
+                });
+            }
+        });
+
+        // 每次档位变化都发送了, 最后发送的告警与保存的档位一致
+        let (version, tier) = decode(tracker.state.load(Ordering::SeqCst));
+        let severities = messenger.severities();
+        assert_eq!(version as usize, severities.len());
+        assert_eq!(
+            Some(&tracker.policy.alert(tier).severity),
+            severities.last()
+        );
+
+        // 所有线程结束后, 先回到 75% 档再降到最低档以下, 最后一条一定是恢复消息
+        tracker.set_value(80);
+        tracker.set_value(50);
+
+        assert_eq!(50, tracker.value());
+        assert_eq!(
+            "Recovered: You are back below 75% of your quota.",
+            messenger.messages().last().unwrap()
+        );
+    }
+
+    #[test]
+    fn out_of_order_changes_wait_for_earlier_ones() {
+        let messenger = Arc::new(MockMessenger::new());
+        let tracker = SyncLimitTracker::new(messenger.clone(), 4);
+
+        // 第 2 次变化(升到 100% 档)先放入队列, 要等第 1 次变化(升到 75% 档)
+        tracker.enqueue(2, Some(2), 4);
+        assert_eq!(0, messenger.calls.call_count());
+
+        tracker.enqueue(1, Some(0), 3);
+        assert_eq!(
+            vec![Severity::Warning, Severity::Error],
+            messenger.severities()
+        );
+    }
+
+    #[test]
+    fn queue_order_survives_version_wraparound() {
+        let messenger = Arc::new(MockMessenger::new());
+        let tracker = SyncLimitTracker::new(messenger.clone(), 100);
+        tracker.pending().next = u32::MAX;
+
+        tracker.enqueue(0, None, 50);
+        tracker.enqueue(u32::MAX, Some(0), 80);
+
+        assert_eq!(
+            vec![Severity::Warning, Severity::Info],
+            messenger.severities()
+        );
+    }
+
+    #[test]
+    fn failed_alerts_are_dead_lettered_and_redelivered() {
+        let messenger = Arc::new(MockMessenger::new());
+        // 第一次发送和第一次重发都失败
+        messenger.fail_next(2, "service unavailable");
+        let tracker = SyncLimitTracker::new(messenger.clone(), 100).with_retry(Retry::none());
+
+        tracker.set_value(100);
+        assert_eq!(1, tracker.dead_letters().len());
+        assert_eq!(0, tracker.redeliver());
+
+        assert_eq!(1, tracker.redeliver());
+        assert!(tracker.dead_letters().is_empty());
+        messenger
+            .calls
+            .expect(call(alert(eq(Severity::Error), any())).times(3));
+        messenger.calls.verify();
+    }
+}
@@ -0,0 +1,377 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+pub use sexpr::ParseError;
+
+pub mod sexpr;
+
+/// 树的节点: 父节点拥有子节点(强引用), 子节点通过弱引用指回父节点
+///
+/// 这样父子之间不会形成引用循环, 释放根节点就会释放整棵树.
+#[derive(Debug)]
+pub struct Node<T> {
+    pub value: T,
+    parent: RefCell<Weak<Node<T>>>,
+    children: RefCell<Vec<Rc<Node<T>>>>,
+}
+
+/// 把节点加到它自己或它的后代下面会形成循环
+#[derive(Debug, PartialEq)]
+pub struct WouldCycle;
+
+impl fmt::Display for WouldCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a node cannot become a child of itself or its descendants"
+        )
+    }
+}
+
+impl Error for WouldCycle {}
+
+impl<T> Node<T> {
+    pub fn new(value: T) -> Rc<Node<T>> {
+        Rc::new(Node {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(vec![]),
+        })
+    }
+
+    pub fn parent(&self) -> Option<Rc<Node<T>>> {
+        self.parent.borrow().upgrade()
+    }
+
+    pub fn children(&self) -> Vec<Rc<Node<T>>> {
+        self.children.borrow().clone()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent().is_none()
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children.borrow().is_empty()
+    }
+
+    /// 把 `child` 加为最后一个子节点; 如果它已有父节点, 先从原父节点移除
+    pub fn add_child(self: &Rc<Self>, child: Rc<Node<T>>) -> Result<(), WouldCycle> {
+        if self.ancestors().any(|node| Rc::ptr_eq(&node, &child)) {
+            return Err(WouldCycle);
+        }
+
+        child.detach();
+        *child.parent.borrow_mut() = Rc::downgrade(self);
+        self.children.borrow_mut().push(child);
+        Ok(())
+    }
+
+    /// 移除子节点并返回它, 被移除的节点成为一棵独立的树
+    pub fn remove_child(&self, child: &Rc<Node<T>>) -> Option<Rc<Node<T>>> {
+        let mut children = self.children.borrow_mut();
+        let index = children.iter().position(|c| Rc::ptr_eq(c, child))?;
+        let child = children.remove(index);
+        *child.parent.borrow_mut() = Weak::new();
+        Some(child)
+    }
+
+    /// 从父节点移除自己, 返回是否原本有父节点
+    pub fn detach(self: &Rc<Self>) -> bool {
+        match self.parent() {
+            Some(parent) => parent.remove_child(self).is_some(),
+            None => false,
+        }
+    }
+
+    /// 从自己开始, 依次返回父节点直到根节点
+    pub fn ancestors(self: &Rc<Self>) -> Ancestors<T> {
+        Ancestors {
+            next: Some(Rc::clone(self)),
+        }
+    }
+
+    /// 从自己到根节点的路径
+    pub fn path_to_root(self: &Rc<Self>) -> Vec<Rc<Node<T>>> {
+        self.ancestors().collect()
+    }
+
+    pub fn root(self: &Rc<Self>) -> Rc<Node<T>> {
+        self.ancestors().last().unwrap()
+    }
+
+    /// 根节点的深度为 0
+    pub fn depth(self: &Rc<Self>) -> usize {
+        self.ancestors().count() - 1
+    }
+
+    /// 深度优先(先序)遍历以自己为根的子树
+    pub fn depth_first(self: &Rc<Self>) -> DepthFirst<T> {
+        DepthFirst {
+            stack: vec![Rc::clone(self)],
+        }
+    }
+
+    /// 广度优先(按层)遍历以自己为根的子树
+    pub fn breadth_first(self: &Rc<Self>) -> BreadthFirst<T> {
+        BreadthFirst {
+            queue: VecDeque::from([Rc::clone(self)]),
+        }
+    }
+}
+
+/// 以缩进的 ASCII 图形显示整棵子树, 每个节点一行:
+///
+/// ```text
+/// 1
+/// |-- 2
+/// |   `-- 4
+/// `-- 3
+/// ```
+impl<T: fmt::Display> fmt::Display for Node<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.value)?;
+        self.fmt_children(f, &mut String::new())
+    }
+}
+
+impl<T: fmt::Display> Node<T> {
+    fn fmt_children(&self, f: &mut fmt::Formatter, prefix: &mut String) -> fmt::Result {
+        let children = self.children.borrow();
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let (branch, indent) = if last {
+                ("`-- ", "    ")
+            } else {
+                ("|-- ", "|   ")
+            };
+            writeln!(f, "{}{}{}", prefix, branch, child.value)?;
+
+            let len = prefix.len();
+            prefix.push_str(indent);
+            child.fmt_children(f, prefix)?;
+            prefix.truncate(len);
+        }
+        Ok(())
+    }
+}
+
+pub struct Ancestors<T> {
+    next: Option<Rc<Node<T>>>,
+}
+
+impl<T> Iterator for Ancestors<T> {
+    type Item = Rc<Node<T>>;
+
+    fn next(&mut self) -> Option<Rc<Node<T>>> {
+        let node = self.next.take()?;
+        self.next = node.parent();
+        Some(node)
+    }
+}
+
+pub struct DepthFirst<T> {
+    stack: Vec<Rc<Node<T>>>,
+}
+
+impl<T> Iterator for DepthFirst<T> {
+    type Item = Rc<Node<T>>;
+
+    fn next(&mut self) -> Option<Rc<Node<T>>> {
+        let node = self.stack.pop()?;
+        // 倒序入栈, 使第一个子节点最先出栈
+        self.stack
+            .extend(node.children.borrow().iter().rev().cloned());
+        Some(node)
+    }
+}
+
+pub struct BreadthFirst<T> {
+    queue: VecDeque<Rc<Node<T>>>,
+}
+
+impl<T> Iterator for BreadthFirst<T> {
+    type Item = Rc<Node<T>>;
+
+    fn next(&mut self) -> Option<Rc<Node<T>>> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children.borrow().iter().cloned());
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(nodes: impl Iterator<Item = Rc<Node<i32>>>) -> Vec<i32> {
+        nodes.map(|node| node.value).collect()
+    }
+
+    ///       1
+    ///     /   \
+    ///    2     3
+    ///   / \     \
+    ///  4   5     6
+    fn sample() -> Vec<Rc<Node<i32>>> {
+        let nodes: Vec<_> = (0..=6).map(Node::new).collect();
+        for (parent, child) in [(1, 2), (1, 3), (2, 4), (2, 5), (3, 6)] {
+            nodes[parent].add_child(Rc::clone(&nodes[child])).unwrap();
+        }
+        nodes
+    }
+
+    #[test]
+    fn render_ascii() {
+        let nodes = sample();
+        nodes[4].add_child(Node::new(7)).unwrap();
+
+        assert_eq!(
+            "\
+1
+|-- 2
+|   |-- 4
+|   |   `-- 7
+|   `-- 5
+`-- 3
+    `-- 6
+",
+            nodes[1].to_string()
+        );
+        assert_eq!("6\n", nodes[6].to_string());
+    }
+
+    #[test]
+    fn render_parsed_config() {
+        let config: Rc<Node<String>> =
+            Node::parse_sexpr("(server (listen 80 443) (log (level debug)))").unwrap();
+
+        assert_eq!(
+            "\
+server
+|-- listen
+|   |-- 80
+|   `-- 443
+`-- log
+    `-- level
+        `-- debug
+",
+            config.to_string()
+        );
+    }
+
+    #[test]
+    fn parent_links() {
+        let nodes = sample();
+
+        assert!(nodes[1].is_root());
+        assert!(Rc::ptr_eq(&nodes[2], &nodes[4].parent().unwrap()));
+        assert_eq!(vec![4, 5], values(nodes[2].children().into_iter()));
+        assert!(nodes[6].is_leaf());
+    }
+
+    #[test]
+    fn traversal_orders() {
+        let nodes = sample();
+
+        assert_eq!(vec![1, 2, 4, 5, 3, 6], values(nodes[1].depth_first()));
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], values(nodes[1].breadth_first()));
+        assert_eq!(vec![3, 6], values(nodes[3].depth_first()));
+    }
+
+    #[test]
+    fn path_to_root() {
+        let nodes = sample();
+
+        assert_eq!(vec![5, 2, 1], values(nodes[5].path_to_root().into_iter()));
+        assert_eq!(2, nodes[5].depth());
+        assert_eq!(1, nodes[5].root().value);
+        assert_eq!(0, nodes[1].depth());
+    }
+
+    #[test]
+    fn remove_child() {
+        let nodes = sample();
+
+        let removed = nodes[1].remove_child(&nodes[2]).unwrap();
+
+        assert!(removed.is_root());
+        assert_eq!(vec![1, 3, 6], values(nodes[1].depth_first()));
+        assert_eq!(vec![2, 4, 5], values(removed.depth_first()));
+        assert_eq!(None, nodes[1].remove_child(&nodes[4]).map(|n| n.value));
+    }
+
+    #[test]
+    fn reparenting_keeps_links_consistent() {
+        let nodes = sample();
+
+        nodes[6].add_child(Rc::clone(&nodes[2])).unwrap();
+
+        assert!(Rc::ptr_eq(&nodes[6], &nodes[2].parent().unwrap()));
+        assert_eq!(vec![3], values(nodes[1].children().into_iter()));
+        assert_eq!(vec![4, 2, 6, 3, 1], values(nodes[4].ancestors()));
+        assert_eq!(vec![1, 3, 6, 2, 4, 5], values(nodes[1].depth_first()));
+        // 2 的强引用: nodes 和新父节点 6
+        assert_eq!(2, Rc::strong_count(&nodes[2]));
+    }
+
+    #[test]
+    fn adding_an_ancestor_is_rejected() {
+        let nodes = sample();
+
+        assert_eq!(Err(WouldCycle), nodes[4].add_child(Rc::clone(&nodes[1])));
+        assert_eq!(Err(WouldCycle), nodes[2].add_child(Rc::clone(&nodes[2])));
+        assert!(nodes[1].is_root());
+        assert_eq!(vec![1, 2, 4, 5, 3, 6], values(nodes[1].depth_first()));
+    }
+
+    #[test]
+    fn counts_follow_ownership() {
+        let leaf = Node::new(3);
+        assert_eq!((1, 0), (Rc::strong_count(&leaf), Rc::weak_count(&leaf)));
+
+        {
+            let branch = Node::new(5);
+            branch.add_child(Rc::clone(&leaf)).unwrap();
+
+            assert_eq!((1, 1), (Rc::strong_count(&branch), Rc::weak_count(&branch)));
+            assert_eq!((2, 0), (Rc::strong_count(&leaf), Rc::weak_count(&leaf)));
+            assert!(Rc::ptr_eq(&branch, &leaf.parent().unwrap()));
+        }
+
+        assert!(leaf.parent().is_none());
+        assert_eq!((1, 0), (Rc::strong_count(&leaf), Rc::weak_count(&leaf)));
+    }
+
+    #[test]
+    fn dropping_root_frees_every_node() {
+        let mut nodes = sample();
+        let root = nodes.remove(1);
+        // 只保留后代的弱引用, 此后只有根节点持有强引用
+        let descendants: Vec<Weak<Node<i32>>> = nodes[1..].iter().map(Rc::downgrade).collect();
+        drop(nodes);
+
+        assert!(descendants.iter().all(|node| node.upgrade().is_some()));
+
+        drop(root);
+
+        // 父节点链接如果是强引用, 这里会因为循环引用而泄漏
+        assert!(descendants.iter().all(|node| node.upgrade().is_none()));
+    }
+
+    #[test]
+    fn iterators_release_their_references() {
+        let nodes = sample();
+
+        let mut iter = nodes[1].breadth_first();
+        iter.next();
+        assert_eq!(3, Rc::strong_count(&nodes[2]));
+        drop(iter);
+
+        assert_eq!(1, Rc::strong_count(&nodes[1]));
+        assert!(nodes[2..].iter().all(|node| Rc::strong_count(node) == 2));
+    }
+}
@@ -0,0 +1,266 @@
+//! 用 S 表达式保存树: 叶子节点写成单独的值, 有子节点的写成 `(值 子节点...)`
+//!
+//! 例如 `(1 (2 4 5) (3 6))`. 含空白、括号、引号或为空的值写成带引号的字符串, 其中 `"` 和 `\` 用 `\` 转义.
+
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::str::{CharIndices, FromStr};
+
+use super::Node;
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// 出错位置在输入中的字节偏移
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at offset {}: {}", self.offset, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+impl<T: fmt::Display> Node<T> {
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        self.write_sexpr(&mut out);
+        out
+    }
+
+    fn write_sexpr(&self, out: &mut String) {
+        let children = self.children.borrow();
+        if children.is_empty() {
+            write_atom(&self.value.to_string(), out);
+            return;
+        }
+
+        out.push('(');
+        write_atom(&self.value.to_string(), out);
+        for child in children.iter() {
+            out.push(' ');
+            child.write_sexpr(out);
+        }
+        out.push(')');
+    }
+}
+
+impl<T: FromStr> Node<T>
+where
+    T::Err: fmt::Display,
+{
+    pub fn parse_sexpr(input: &str) -> Result<Rc<Node<T>>, ParseError> {
+        let mut tokens = Tokens {
+            chars: input.char_indices().peekable(),
+            end: input.len(),
+        };
+
+        let root = parse_node(&mut tokens)?;
+        match tokens.next()? {
+            None => Ok(root),
+            Some((offset, _)) => Err(error(offset, "unexpected input after the tree")),
+        }
+    }
+}
+
+fn write_atom(atom: &str, out: &mut String) {
+    let needs_quotes = atom.is_empty()
+        || atom
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\\'));
+
+    if !needs_quotes {
+        out.push_str(atom);
+        return;
+    }
+
+    out.push('"');
+    for c in atom.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+fn error(offset: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        offset,
+        message: message.into(),
+    }
+}
+
+enum Token {
+    Open,
+    Close,
+    Atom(String),
+}
+
+struct Tokens<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    end: usize,
+}
+
+impl Tokens<'_> {
+    /// 返回下一个记号及其偏移, 输入结束时返回 `None`
+    fn next(&mut self) -> Result<Option<(usize, Token)>, ParseError> {
+        while self.chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+
+        let Some((start, c)) = self.chars.next() else {
+            return Ok(None);
+        };
+
+        let token = match c {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '"' => Token::Atom(self.quoted(start)?),
+            _ => {
+                let mut atom = String::from(c);
+                while let Some((_, c)) = self
+                    .chars
+                    .next_if(|&(_, c)| !c.is_whitespace() && !matches!(c, '(' | ')' | '"'))
+                {
+                    atom.push(c);
+                }
+                Token::Atom(atom)
+            }
+        };
+        Ok(Some((start, token)))
+    }
+
+    fn quoted(&mut self, start: usize) -> Result<String, ParseError> {
+        let mut atom = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(atom),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, c)) => atom.push(c),
+                    None => break,
+                },
+                Some((_, c)) => atom.push(c),
+                None => break,
+            }
+        }
+        Err(error(start, "unterminated string"))
+    }
+}
+
+fn parse_node<T: FromStr>(tokens: &mut Tokens) -> Result<Rc<Node<T>>, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    match tokens.next()? {
+        Some((offset, Token::Atom(atom))) => parse_value(offset, &atom).map(Node::new),
+        Some((_, Token::Open)) => {
+            let node = match tokens.next()? {
+                Some((offset, Token::Atom(atom))) => Node::new(parse_value(offset, &atom)?),
+                Some((offset, _)) => return Err(error(offset, "expected a value after '('")),
+                None => return Err(error(tokens.end, "expected a value after '('")),
+            };
+
+            loop {
+                match tokens.chars.peek() {
+                    Some(&(_, c)) if c.is_whitespace() => {
+                        tokens.chars.next();
+                    }
+                    Some(&(_, ')')) => {
+                        tokens.chars.next();
+                        return Ok(node);
+                    }
+                    Some(_) => {
+                        // 新建的节点不可能是 node 的祖先, 不会失败
+                        node.add_child(parse_node(tokens)?).unwrap();
+                    }
+                    None => return Err(error(tokens.end, "missing ')'")),
+                }
+            }
+        }
+        Some((offset, Token::Close)) => Err(error(offset, "unexpected ')'")),
+        None => Err(error(tokens.end, "expected a node")),
+    }
+}
+
+fn parse_value<T: FromStr>(offset: usize, atom: &str) -> Result<T, ParseError>
+where
+    T::Err: fmt::Display,
+{
+    atom.parse()
+        .map_err(|e| error(offset, format!("invalid value {:?}: {}", atom, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Rc<Node<i32>>, ParseError> {
+        Node::parse_sexpr(input)
+    }
+
+    #[test]
+    fn write_nested() {
+        let root = Node::new(1);
+        let two = Node::new(2);
+        root.add_child(Rc::clone(&two)).unwrap();
+        root.add_child(Node::new(3)).unwrap();
+        two.add_child(Node::new(4)).unwrap();
+
+        assert_eq!("(1 (2 4) 3)", root.to_sexpr());
+        assert_eq!("4", two.children()[0].to_sexpr());
+    }
+
+    #[test]
+    fn parse_nested() {
+        let root = parse(" (1\n  (2 4 5)\n  (3 6)) ").unwrap();
+
+        assert_eq!("(1 (2 4 5) (3 6))", root.to_sexpr());
+        assert_eq!(2, root.children().len());
+        assert!(Rc::ptr_eq(&root, &root.children()[1].parent().unwrap()));
+    }
+
+    #[test]
+    fn single_value_in_parentheses_is_a_leaf() {
+        assert_eq!("7", parse("(7)").unwrap().to_sexpr());
+        assert_eq!("7", parse("7").unwrap().to_sexpr());
+    }
+
+    #[test]
+    fn quoted_values_round_trip() {
+        let input =
+            r#"(server (listen 0.0.0.0:80) (name "say \"hi\"") (path "C:\\data") (empty ""))"#;
+
+        let root: Rc<Node<String>> = Node::parse_sexpr(input).unwrap();
+
+        let listen = &root.children()[0];
+        assert_eq!("0.0.0.0:80", listen.children()[0].value);
+        assert_eq!("say \"hi\"", root.children()[1].children()[0].value);
+        assert_eq!("C:\\data", root.children()[2].children()[0].value);
+        assert_eq!("", root.children()[3].children()[0].value);
+        assert_eq!(input, root.to_sexpr());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error(0, "expected a node"), parse("").unwrap_err());
+        assert_eq!(error(6, "missing ')'"), parse("(1 (2)").unwrap_err());
+        assert_eq!(error(0, "unexpected ')'"), parse(")").unwrap_err());
+        assert_eq!(
+            error(2, "unexpected input after the tree"),
+            parse("1 2").unwrap_err()
+        );
+        assert_eq!(
+            error(1, "expected a value after '('"),
+            parse("((1))").unwrap_err()
+        );
+        assert_eq!(
+            error(3, "invalid value \"x\": invalid digit found in string"),
+            parse("(1 x)").unwrap_err()
+        );
+        let err = Node::<String>::parse_sexpr("(a \"b").unwrap_err();
+        assert_eq!("at offset 3: unterminated string", err.to_string());
+    }
+}
@@ -0,0 +1,352 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::delivery::{self, DeadLetters, Retry};
+use crate::{Messenger, Policy};
+
+/// 时间来源, 返回从某个固定起点开始经过的时间
+///
+/// 测试中可以用 `ManualClock` 手动推进时间.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// 只有调用 `advance` 时才会前进的时钟
+#[derive(Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+enum Meter {
+    /// 记录最近 `period` 内的每一批请求
+    SlidingWindow { events: VecDeque<(Duration, usize)> },
+    /// 令牌桶: 容量为 `limit`, 每个 `period` 匀速补满; 令牌可以为负, 表示超出的部分
+    TokenBucket { tokens: f64, refilled_at: Duration },
+}
+
+/// 按时间窗口跟踪速率类配额, 例如"每分钟请求数"
+///
+/// 与 `LimitTracker` 一样按 `Policy` 的各档告警, 用量为窗口内的请求数(滑动窗口)
+/// 或已消耗的令牌数(令牌桶), 上限为 `limit`.
+pub struct RateTracker<'a, T: Messenger, C: Clock> {
+    messenger: &'a T,
+    clock: &'a C,
+    limit: usize,
+    period: Duration,
+    meter: Meter,
+    policy: Policy,
+    alerted: Option<usize>,
+    retry: Retry,
+    dead_letters: DeadLetters,
+}
+
+impl<'a, T, C> RateTracker<'a, T, C>
+where
+    T: Messenger,
+    C: Clock,
+{
+    /// 每个 `period` 内最多 `limit` 个请求, 按滑动窗口统计
+    pub fn sliding_window(
+        messenger: &'a T,
+        clock: &'a C,
+        limit: usize,
+        period: Duration,
+    ) -> RateTracker<'a, T, C> {
+        let meter = Meter::SlidingWindow {
+            events: VecDeque::new(),
+        };
+        RateTracker::with_meter(messenger, clock, limit, period, meter)
+    }
+
+    /// 容量为 `limit` 的令牌桶, 每个 `period` 补充 `limit` 个令牌
+    pub fn token_bucket(
+        messenger: &'a T,
+        clock: &'a C,
+        limit: usize,
+        period: Duration,
+    ) -> RateTracker<'a, T, C> {
+        let meter = Meter::TokenBucket {
+            tokens: limit as f64,
+            refilled_at: clock.now(),
+        };
+        RateTracker::with_meter(messenger, clock, limit, period, meter)
+    }
+
+    fn with_meter(
+        messenger: &'a T,
+        clock: &'a C,
+        limit: usize,
+        period: Duration,
+        meter: Meter,
+    ) -> RateTracker<'a, T, C> {
+        RateTracker {
+            messenger,
+            clock,
+            limit,
+            period,
+            meter,
+            policy: Policy::default(),
+            alerted: None,
+            retry: Retry::default(),
+            dead_letters: DeadLetters::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: Policy) -> RateTracker<'a, T, C> {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Retry) -> RateTracker<'a, T, C> {
+        self.retry = retry;
+        self
+    }
+
+    /// 重试后仍未发送出去的消息
+    pub fn dead_letters(&self) -> &DeadLetters {
+        &self.dead_letters
+    }
+
+    /// 重新发送死信, 返回成功发送的数量
+    pub fn redeliver(&mut self) -> usize {
+        self.dead_letters.redeliver(self.messenger, self.retry)
+    }
+
+    /// 记录此刻发生的 `n` 个请求
+    pub fn record(&mut self, n: usize) {
+        let now = self.clock.now();
+        self.advance(now);
+
+        match &mut self.meter {
+            Meter::SlidingWindow { events } => events.push_back((now, n)),
+            Meter::TokenBucket { tokens, .. } => *tokens -= n as f64,
+        }
+
+        self.check();
+    }
+
+    /// 不记录请求, 只根据流逝的时间重新计算用量, 用量下降时可能发出降档或恢复消息
+    pub fn tick(&mut self) {
+        self.advance(self.clock.now());
+        self.check();
+    }
+
+    /// 当前用量
+    pub fn usage(&mut self) -> usize {
+        self.advance(self.clock.now());
+        self.current_usage()
+    }
+
+    fn advance(&mut self, now: Duration) {
+        match &mut self.meter {
+            Meter::SlidingWindow { events } => {
+                while let Some(&(at, _)) = events.front() {
+                    if at + self.period > now {
+                        break;
+                    }
+                    events.pop_front();
+                }
+            }
+            Meter::TokenBucket {
+                tokens,
+                refilled_at,
+            } => {
+                let elapsed = now.saturating_sub(*refilled_at).as_secs_f64();
+                let rate = self.limit as f64 / self.period.as_secs_f64();
+                *tokens = (*tokens + elapsed * rate).min(self.limit as f64);
+                *refilled_at = now;
+            }
+        }
+    }
+
+    fn current_usage(&self) -> usize {
+        match &self.meter {
+            Meter::SlidingWindow { events } => events.iter().map(|&(_, n)| n).sum(),
+            Meter::TokenBucket { tokens, .. } => {
+                (self.limit as f64 - tokens).ceil().max(0.0) as usize
+            }
+        }
+    }
+
+    fn check(&mut self) {
+        let usage = self.current_usage();
+        let transition = self.policy.transition(&mut self.alerted, usage, self.limit);
+
+        if let Some(threshold) = transition {
+            let message = threshold.render(usage, self.limit);
+            if let Err(letter) =
+                delivery::deliver(self.messenger, self.retry, threshold.severity, &message)
+            {
+                self.dead_letters.push(letter);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockMessenger;
+    use crate::Severity;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn manual_clock_advances() {
+        let clock = ManualClock::new();
+        clock.advance(Duration::from_secs(2));
+        clock.advance(Duration::from_millis(500));
+
+        assert_eq!(Duration::from_millis(2500), clock.now());
+    }
+
+    #[test]
+    fn sliding_window_counts_recent_requests() {
+        let messenger = MockMessenger::new();
+        let clock = ManualClock::new();
+        let mut tracker = RateTracker::sliding_window(&messenger, &clock, 100, MINUTE);
+
+        tracker.record(80);
+        clock.advance(Duration::from_secs(30));
+        tracker.record(15);
+        assert_eq!(95, tracker.usage());
+
+        // 第一批请求滑出窗口
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(15, tracker.usage());
+        tracker.tick();
+
+        assert_eq!(
+            vec![Severity::Warning, Severity::Urgent, Severity::Info],
+            messenger.severities()
+        );
+    }
+
+    #[test]
+    fn sliding_window_over_limit() {
+        let messenger = MockMessenger::new();
+        let clock = ManualClock::new();
+        let mut tracker = RateTracker::sliding_window(&messenger, &clock, 10, MINUTE);
+
+        for _ in 0..12 {
+            tracker.record(1);
+            clock.advance(Duration::from_secs(1));
+        }
+
+        assert_eq!(12, tracker.usage());
+        assert_eq!(
+            vec![Severity::Warning, Severity::Urgent, Severity::Error],
+            messenger.severities()
+        );
+        assert_eq!("Error: You are over your quota!", messenger.messages()[2]);
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let messenger = MockMessenger::new();
+        let clock = ManualClock::new();
+        let mut tracker = RateTracker::token_bucket(&messenger, &clock, 100, MINUTE);
+
+        tracker.record(80);
+        assert_eq!(80, tracker.usage());
+
+        // 半分钟补充 50 个令牌
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(30, tracker.usage());
+        tracker.tick();
+
+        tracker.record(100);
+        assert_eq!(130, tracker.usage());
+
+        // 令牌不会超过容量
+        clock.advance(Duration::from_secs(600));
+        assert_eq!(0, tracker.usage());
+
+        assert_eq!(
+            vec![Severity::Warning, Severity::Info, Severity::Error],
+            messenger.severities()
+        );
+    }
+
+    #[test]
+    fn failed_rate_alerts_can_be_redelivered() {
+        let messenger = MockMessenger::new();
+        messenger.fail_next(1, "service unavailable");
+        let clock = ManualClock::new();
+        let mut tracker = RateTracker::sliding_window(&messenger, &clock, 100, MINUTE);
+
+        tracker.record(80);
+        assert_eq!(1, tracker.dead_letters().len());
+
+        assert_eq!(1, tracker.redeliver());
+        assert!(tracker.dead_letters().is_empty());
+        assert_eq!(
+            vec![
+                "Warning: You've used up over 75% of your quota!",
+                "Warning: You've used up over 75% of your quota!",
+            ],
+            messenger.messages()
+        );
+    }
+
+    #[test]
+    fn custom_policy_applies_to_rates() {
+        let messenger = MockMessenger::new();
+        let clock = ManualClock::new();
+        let policy = Policy::new(vec![crate::Threshold::new(
+            50,
+            Severity::Warning,
+            "{value} requests in the last minute (limit {max})",
+        )]);
+        let mut tracker =
+            RateTracker::sliding_window(&messenger, &clock, 20, MINUTE).with_policy(policy);
+
+        tracker.record(10);
+
+        assert_eq!(
+            "10 requests in the last minute (limit 20)",
+            messenger.messages()[0]
+        );
+    }
+}